@@ -0,0 +1,24 @@
+pub mod commit_kinds;
+pub mod comparison;
+pub mod coupling;
+pub mod hotspots;
+pub mod language;
+pub mod ownership;
+pub mod releases;
+pub mod rollups;
+pub mod snapshots;
+pub mod timeline;
+
+/// ファイルパスから指定した深さのディレクトリを取得（ルート直下は"."）
+///
+/// 例: `directory_prefix("src/git/diff.rs", 1)` → `"src"`
+pub fn directory_prefix(file_path: &str, depth: usize) -> String {
+    let components: Vec<&str> = file_path.split('/').collect();
+    let dir_len = (components.len() - 1).min(depth);
+
+    if dir_len == 0 {
+        ".".to_string()
+    } else {
+        components[..dir_len].join("/")
+    }
+}
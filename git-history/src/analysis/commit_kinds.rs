@@ -0,0 +1,27 @@
+use crate::database::Database;
+use crate::error::Result;
+
+/// 一括変更・整形コミットを判定するしきい値
+#[derive(Debug, Clone, Copy)]
+pub struct CommitKindOptions {
+    /// これより多くのファイルを変更したコミットを `BULK` とする
+    pub bulk_files: usize,
+
+    /// 変更行のうち空白のみの変更がこの割合以上のコミットを `FORMATTING` とする
+    /// （`--ignore-whitespace` で解析したコミットのみ判定できる）
+    pub whitespace_share: f64,
+}
+
+impl Default for CommitKindOptions {
+    fn default() -> Self {
+        Self {
+            bulk_files: 100,
+            whitespace_share: 0.9,
+        }
+    }
+}
+
+/// commit_kinds テーブルを再計算し、分類したコミット数を返す
+pub fn refresh_commit_kinds(database: &mut Database, options: &CommitKindOptions) -> Result<usize> {
+    database.refresh_commit_kinds(options.bulk_files, options.whitespace_share)
+}
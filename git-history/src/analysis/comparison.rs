@@ -0,0 +1,458 @@
+use crate::analysis::directory_prefix;
+use crate::config;
+use crate::database::{ChurnRecord, Database};
+use crate::error::{GitHistoryError, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// 比較のオプション
+#[derive(Debug, Clone)]
+pub struct ComparisonOptions {
+    /// ディレクトリ集計の深さ
+    pub depth: usize,
+
+    /// ホットスポットとして扱う上位件数（変更回数の順位）
+    pub top: usize,
+}
+
+impl Default for ComparisonOptions {
+    fn default() -> Self {
+        Self { depth: 2, top: 10 }
+    }
+}
+
+/// 比較する期間（Unix timestamp、両端を含む。Noneは制限なし）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Period {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl Period {
+    /// `START..END`（片側は省略可）または四半期 `2024-Q1` を解析
+    pub fn parse(value: &str) -> Result<Self> {
+        if let Some(period) = parse_quarter(value) {
+            return Ok(period);
+        }
+
+        let (start, end) = value.split_once("..").ok_or_else(|| {
+            GitHistoryError::ConfigError(format!(
+                "Invalid period '{}' (expected START..END or YYYY-Qn)",
+                value
+            ))
+        })?;
+        let parse = |date: &str, end_of_day: bool| {
+            let date = date.trim();
+            (!date.is_empty())
+                .then(|| config::parse_date(date, end_of_day))
+                .transpose()
+        };
+
+        Ok(Self {
+            since: parse(start, false)?,
+            until: parse(end, true)?,
+        })
+    }
+}
+
+/// `2024-Q1` / `2024Q1` を期間に変換
+fn parse_quarter(value: &str) -> Option<Period> {
+    let (year, quarter) = value
+        .to_ascii_uppercase()
+        .split_once('Q')
+        .map(|(year, quarter)| {
+            (
+                year.trim_end_matches('-').parse::<i32>().ok(),
+                quarter.parse::<u32>().ok(),
+            )
+        })?;
+    let (year, quarter) = (year?, quarter.filter(|q| (1..=4).contains(q))?);
+
+    let start = NaiveDate::from_ymd_opt(year, (quarter - 1) * 3 + 1, 1)?;
+    let next = if quarter == 4 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 3, 1)?
+    };
+    Some(Period {
+        since: Some(start.and_hms_opt(0, 0, 0)?.and_utc().timestamp()),
+        until: Some(next.and_hms_opt(0, 0, 0)?.and_utc().timestamp() - 1),
+    })
+}
+
+/// 比較する一方の側（データベースと期間）
+pub struct ComparisonSide<'a> {
+    /// 表示用の名前（期間の指定やDBファイル名）
+    pub label: String,
+    pub database: &'a Database,
+    pub period: Period,
+}
+
+/// 一方の側の集計
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeriodSummary {
+    pub label: String,
+    pub commits: usize,
+    pub authors: usize,
+    pub files_changed: usize,
+    pub lines_added: i64,
+    pub lines_deleted: i64,
+}
+
+impl PeriodSummary {
+    /// 追加行数 + 削除行数
+    pub fn churn(&self) -> i64 {
+        self.lines_added + self.lines_deleted
+    }
+}
+
+/// ホットスポット（変更回数の上位）の入れ替わり
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotChange {
+    pub file_path: String,
+
+    /// 比較元での順位（上位に入っていなければNone）
+    pub base_rank: Option<usize>,
+
+    /// 比較先での順位
+    pub target_rank: Option<usize>,
+
+    pub base_changes: i64,
+    pub target_changes: i64,
+}
+
+/// 参加・離脱した作成者
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorChange {
+    pub author_name: String,
+    pub author_email: String,
+
+    /// その側の期間でのコミット数
+    pub commits: usize,
+
+    /// その側の期間での最終コミット日時 (Unix timestamp)
+    pub last_commit_date: i64,
+}
+
+/// ディレクトリごとの変化
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryChange {
+    pub path: String,
+    pub base_churn: i64,
+    pub target_churn: i64,
+
+    /// 追加行数 − 削除行数（正なら成長、負なら縮小）
+    pub base_net_lines: i64,
+    pub target_net_lines: i64,
+}
+
+impl DirectoryChange {
+    /// チャーンの増減
+    pub fn churn_delta(&self) -> i64 {
+        self.target_churn - self.base_churn
+    }
+}
+
+/// 比較の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonReport {
+    pub base: PeriodSummary,
+    pub target: PeriodSummary,
+
+    /// 比較先で新たに上位に入ったファイル
+    pub new_hotspots: Vec<HotspotChange>,
+
+    /// 比較元では上位だったが比較先では外れたファイル
+    pub cooled_hotspots: Vec<HotspotChange>,
+
+    /// 比較先にのみコミットがある作成者
+    pub joined_authors: Vec<AuthorChange>,
+
+    /// 比較元にのみコミットがある作成者
+    pub left_authors: Vec<AuthorChange>,
+
+    /// チャーンの増減が大きい順のディレクトリ
+    pub directories: Vec<DirectoryChange>,
+}
+
+/// 2つの期間（または2つのデータベース）を比較
+pub fn compare(
+    base: &ComparisonSide,
+    target: &ComparisonSide,
+    options: &ComparisonOptions,
+) -> Result<ComparisonReport> {
+    let base_records = base
+        .database
+        .get_churn_records_between(base.period.since, base.period.until)?;
+    let target_records = target
+        .database
+        .get_churn_records_between(target.period.since, target.period.until)?;
+
+    Ok(compute_comparison(
+        (&base.label, &base_records),
+        (&target.label, &target_records),
+        options,
+    ))
+}
+
+/// チャーン記録から比較結果を計算
+pub fn compute_comparison(
+    (base_label, base): (&str, &[ChurnRecord]),
+    (target_label, target): (&str, &[ChurnRecord]),
+    options: &ComparisonOptions,
+) -> ComparisonReport {
+    let (new_hotspots, cooled_hotspots) = hotspot_changes(base, target, options.top);
+    let base_authors = author_stats(base);
+    let target_authors = author_stats(target);
+
+    ComparisonReport {
+        base: summarize(base_label, base),
+        target: summarize(target_label, target),
+        new_hotspots,
+        cooled_hotspots,
+        joined_authors: authors_only_in(&target_authors, &base_authors),
+        left_authors: authors_only_in(&base_authors, &target_authors),
+        directories: directory_changes(base, target, options),
+    }
+}
+
+/// 一方の側の件数と行数を集計
+fn summarize(label: &str, records: &[ChurnRecord]) -> PeriodSummary {
+    let commits: HashSet<&str> = records.iter().map(|r| r.commit_hash.as_str()).collect();
+    let authors: HashSet<String> = records
+        .iter()
+        .map(|r| r.author_email.to_lowercase())
+        .collect();
+    let files: HashSet<&str> = records.iter().map(|r| r.file_path.as_str()).collect();
+
+    PeriodSummary {
+        label: label.to_string(),
+        commits: commits.len(),
+        authors: authors.len(),
+        files_changed: files.len(),
+        lines_added: records.iter().map(|r| r.lines_added as i64).sum(),
+        lines_deleted: records.iter().map(|r| r.lines_deleted as i64).sum(),
+    }
+}
+
+/// ファイルを変更回数（同数ならチャーン、パス）の順に並べた (パス, 変更回数) の一覧
+fn rank_files(records: &[ChurnRecord]) -> Vec<(String, i64)> {
+    let mut stats: HashMap<&str, (i64, i64)> = HashMap::new();
+    for record in records {
+        let entry = stats.entry(&record.file_path).or_default();
+        entry.0 += 1;
+        entry.1 += record.lines_changed as i64;
+    }
+
+    let mut ranked: Vec<(&str, (i64, i64))> = stats.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .map(|(path, (changes, _))| (path.to_string(), changes))
+        .collect()
+}
+
+/// 上位 `top` 件のホットスポットの入れ替わり（新たに入ったもの, 外れたもの）
+fn hotspot_changes(
+    base: &[ChurnRecord],
+    target: &[ChurnRecord],
+    top: usize,
+) -> (Vec<HotspotChange>, Vec<HotspotChange>) {
+    let base_ranked = rank_files(base);
+    let target_ranked = rank_files(target);
+    let position = |ranked: &[(String, i64)], path: &str| {
+        ranked
+            .iter()
+            .position(|(p, _)| p == path)
+            .map(|i| (i + 1, ranked[i].1))
+    };
+
+    // 上位に入っていない側は順位をNone、変更回数は期間内の実数にする
+    let change = |path: &str| {
+        let base = position(&base_ranked, path);
+        let target = position(&target_ranked, path);
+        HotspotChange {
+            file_path: path.to_string(),
+            base_rank: base.map(|(rank, _)| rank).filter(|rank| *rank <= top),
+            target_rank: target.map(|(rank, _)| rank).filter(|rank| *rank <= top),
+            base_changes: base.map_or(0, |(_, changes)| changes),
+            target_changes: target.map_or(0, |(_, changes)| changes),
+        }
+    };
+
+    let new_hotspots = target_ranked
+        .iter()
+        .take(top)
+        .map(|(path, _)| change(path))
+        .filter(|c| c.base_rank.is_none())
+        .collect();
+    let cooled_hotspots = base_ranked
+        .iter()
+        .take(top)
+        .map(|(path, _)| change(path))
+        .filter(|c| c.target_rank.is_none())
+        .collect();
+
+    (new_hotspots, cooled_hotspots)
+}
+
+/// 作成者（メールアドレスの小文字）ごとのコミット数と最終コミット
+fn author_stats(records: &[ChurnRecord]) -> HashMap<String, AuthorChange> {
+    let mut commits: HashMap<String, HashSet<&str>> = HashMap::new();
+    let mut authors: HashMap<String, AuthorChange> = HashMap::new();
+
+    for record in records {
+        let key = record.author_email.to_lowercase();
+        commits
+            .entry(key.clone())
+            .or_default()
+            .insert(&record.commit_hash);
+
+        let author = authors.entry(key).or_insert_with(|| AuthorChange {
+            author_name: record.author_name.clone(),
+            author_email: record.author_email.clone(),
+            commits: 0,
+            last_commit_date: record.commit_date,
+        });
+        // 名前は最新のコミットのものを使う
+        if record.commit_date >= author.last_commit_date {
+            author.author_name = record.author_name.clone();
+            author.last_commit_date = record.commit_date;
+        }
+    }
+
+    for (key, author) in authors.iter_mut() {
+        author.commits = commits.get(key).map_or(0, HashSet::len);
+    }
+    authors
+}
+
+/// `side` にのみいる作成者（コミット数の多い順）
+fn authors_only_in(
+    side: &HashMap<String, AuthorChange>,
+    other: &HashMap<String, AuthorChange>,
+) -> Vec<AuthorChange> {
+    let mut authors: Vec<AuthorChange> = side
+        .iter()
+        .filter(|(key, _)| !other.contains_key(*key))
+        .map(|(_, author)| author.clone())
+        .collect();
+    authors.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then_with(|| a.author_email.cmp(&b.author_email))
+    });
+    authors
+}
+
+/// ディレクトリごとのチャーンと増減行数（チャーンの増減が大きい順）
+fn directory_changes(
+    base: &[ChurnRecord],
+    target: &[ChurnRecord],
+    options: &ComparisonOptions,
+) -> Vec<DirectoryChange> {
+    let mut directories: HashMap<String, DirectoryChange> = HashMap::new();
+    for (records, is_base) in [(base, true), (target, false)] {
+        for record in records {
+            let path = directory_prefix(&record.file_path, options.depth);
+            let directory = directories
+                .entry(path.clone())
+                .or_insert_with(|| DirectoryChange {
+                    path,
+                    base_churn: 0,
+                    target_churn: 0,
+                    base_net_lines: 0,
+                    target_net_lines: 0,
+                });
+
+            let churn = record.lines_changed as i64;
+            let net = (record.lines_added - record.lines_deleted) as i64;
+            if is_base {
+                directory.base_churn += churn;
+                directory.base_net_lines += net;
+            } else {
+                directory.target_churn += churn;
+                directory.target_net_lines += net;
+            }
+        }
+    }
+
+    let mut directories: Vec<DirectoryChange> = directories.into_values().collect();
+    directories.sort_by(|a, b| {
+        b.churn_delta()
+            .abs()
+            .cmp(&a.churn_delta().abs())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAN_2024: i64 = 1_704_067_200;
+    const APR_2024: i64 = 1_711_929_600;
+    const OCT_2024: i64 = 1_727_740_800;
+    const JAN_2025: i64 = 1_735_689_600;
+
+    #[test]
+    fn quarters_cover_whole_months() {
+        let q1 = Period {
+            since: Some(JAN_2024),
+            until: Some(APR_2024 - 1),
+        };
+        assert_eq!(parse_quarter("2024-Q1"), Some(q1));
+        assert_eq!(parse_quarter("2024q1"), Some(q1));
+        // 第4四半期は翌年の1月1日の直前まで
+        assert_eq!(
+            parse_quarter("2024-Q4"),
+            Some(Period {
+                since: Some(OCT_2024),
+                until: Some(JAN_2025 - 1),
+            })
+        );
+
+        for invalid in ["2024-Q0", "2024-Q5", "Q1", "2024-01", "abcd-Q1"] {
+            assert_eq!(parse_quarter(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn ranges_include_the_whole_end_day_and_allow_open_ends() {
+        assert_eq!(
+            Period::parse("2024-01-01..2024-01-31").unwrap(),
+            Period {
+                since: Some(JAN_2024),
+                until: Some(JAN_2024 + 31 * 86_400 - 1),
+            }
+        );
+        assert_eq!(
+            Period::parse("..2024-01-01").unwrap(),
+            Period {
+                since: None,
+                until: Some(JAN_2024 + 86_400 - 1),
+            }
+        );
+        assert_eq!(
+            Period::parse(" 2024-01-01 .. ").unwrap(),
+            Period {
+                since: Some(JAN_2024),
+                until: None,
+            }
+        );
+        assert_eq!(Period::parse("2024-Q2").unwrap().since, Some(APR_2024));
+    }
+
+    #[test]
+    fn invalid_periods_are_config_errors() {
+        for invalid in ["2024-01-01", "2024-13-01..", "yesterday..today"] {
+            let error = Period::parse(invalid).unwrap_err();
+            assert!(
+                matches!(error, GitHistoryError::ConfigError(_)),
+                "{invalid}"
+            );
+        }
+    }
+}
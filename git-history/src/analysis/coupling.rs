@@ -0,0 +1,65 @@
+use crate::database::{Database, FileCoupling};
+use crate::error::Result;
+use std::path::Path;
+
+/// 同時変更分析のオプション
+#[derive(Debug, Clone)]
+pub struct CouplingOptions {
+    /// これより多くのファイルを変更したコミットは除外（一括置換などのノイズ対策）
+    pub max_files_per_commit: usize,
+
+    /// 集計対象とする最小の同時変更回数
+    pub min_co_changes: usize,
+
+    /// 表示する最小の確信度（どちらか大きい方で判定）
+    pub min_confidence: f64,
+
+    /// 異なるディレクトリにあるファイルの組み合わせのみに絞る
+    pub cross_directory_only: bool,
+}
+
+impl Default for CouplingOptions {
+    fn default() -> Self {
+        Self {
+            max_files_per_commit: 50,
+            min_co_changes: 3,
+            min_confidence: 0.0,
+            cross_directory_only: false,
+        }
+    }
+}
+
+/// file_coupling テーブルを再計算して結果を取得
+pub fn analyze_coupling(
+    database: &mut Database,
+    options: &CouplingOptions,
+) -> Result<Vec<FileCoupling>> {
+    database.refresh_file_coupling(options.max_files_per_commit, options.min_co_changes)?;
+
+    let couplings = database.get_file_couplings(options.min_confidence)?;
+    Ok(filter_directories(couplings, options))
+}
+
+/// file_coupling テーブルを更新せずに同時変更を集計
+pub fn compute_coupling(
+    database: &Database,
+    options: &CouplingOptions,
+) -> Result<Vec<FileCoupling>> {
+    let couplings = database.compute_file_couplings(
+        options.max_files_per_commit,
+        options.min_co_changes,
+        options.min_confidence,
+    )?;
+    Ok(filter_directories(couplings, options))
+}
+
+/// `cross_directory_only` の場合は同じディレクトリの組み合わせを除く
+fn filter_directories(
+    mut couplings: Vec<FileCoupling>,
+    options: &CouplingOptions,
+) -> Vec<FileCoupling> {
+    if options.cross_directory_only {
+        couplings.retain(|c| Path::new(&c.file_a).parent() != Path::new(&c.file_b).parent());
+    }
+    couplings
+}
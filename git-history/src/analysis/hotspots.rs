@@ -0,0 +1,143 @@
+use crate::analysis::language::{self, LanguageKind};
+use crate::database::{Database, Hotspot};
+use crate::error::Result;
+use crate::git::{GitRepository, tree};
+
+/// ホットスポット分析のオプション
+#[derive(Debug, Clone)]
+pub struct HotspotOptions {
+    /// 複雑度を計算するリビジョン
+    pub revision: String,
+
+    /// プログラミング言語以外のファイルも結果に含める（スコアは0）
+    pub include_non_code: bool,
+}
+
+impl Default for HotspotOptions {
+    fn default() -> Self {
+        Self {
+            revision: "HEAD".to_string(),
+            include_non_code: false,
+        }
+    }
+}
+
+/// ホットスポットを計算して hotspots テーブルに保存
+pub fn analyze_hotspots(
+    git_repo: &GitRepository,
+    database: &mut Database,
+    options: &HotspotOptions,
+) -> Result<Vec<Hotspot>> {
+    let hotspots = compute_hotspots(git_repo, database, options)?;
+    database.replace_hotspots(&hotspots)?;
+    Ok(hotspots)
+}
+
+/// hotspots テーブルを更新せずにホットスポットを計算（スコアの高い順）
+///
+/// スコアは「変更回数 / 最大変更回数」×「複雑度 / 最大複雑度」で、0.0〜1.0の値をとる。
+pub fn compute_hotspots(
+    git_repo: &GitRepository,
+    database: &Database,
+    options: &HotspotOptions,
+) -> Result<Vec<Hotspot>> {
+    let (commit_oid, head_tree) = git_repo.revision_tree(&options.revision)?;
+    let revision = commit_oid.to_string();
+
+    let mut hotspots = Vec::new();
+    for stats in database.get_file_change_stats()? {
+        let language = language::detect_language(&stats.file_path);
+        if language.kind != LanguageKind::Code && !options.include_non_code {
+            continue;
+        }
+
+        // 指定リビジョンに存在しないファイル（削除済み）とバイナリは対象外
+        let path = stats
+            .file_path_raw
+            .as_deref()
+            .unwrap_or(stats.file_path.as_bytes());
+        let Some(content) = tree::read_blob(git_repo.inner(), &head_tree, path)? else {
+            continue;
+        };
+        if language::is_binary(&content) {
+            continue;
+        }
+
+        let metrics = language::measure_complexity(&String::from_utf8_lossy(&content), language);
+
+        hotspots.push(Hotspot {
+            file_path: stats.file_path,
+            language: language.name.to_string(),
+            change_count: stats.change_count,
+            lines_changed: stats.lines_changed,
+            lines: metrics.lines as i64,
+            code_lines: metrics.code_lines as i64,
+            max_depth: metrics.max_depth as i64,
+            mean_depth: metrics.mean_depth,
+            functions: metrics.functions as i64,
+            complexity: metrics.complexity(language),
+            score: 0.0,
+            revision: revision.clone(),
+        });
+    }
+
+    // 変更頻度と複雑度をそれぞれ最大値で正規化して掛け合わせる
+    let max_changes = hotspots.iter().map(|h| h.change_count).max().unwrap_or(0);
+    let max_complexity = hotspots.iter().map(|h| h.complexity).fold(0.0, f64::max);
+    if max_changes > 0 && max_complexity > 0.0 {
+        for hotspot in &mut hotspots {
+            hotspot.score = (hotspot.change_count as f64 / max_changes as f64)
+                * (hotspot.complexity / max_complexity);
+        }
+    }
+
+    hotspots.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.change_count.cmp(&a.change_count))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+
+    Ok(hotspots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::config::Config;
+    use git2::{Repository, Signature, Time};
+
+    #[test]
+    fn files_with_escaped_names_are_measured() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("repo");
+        let repo = Repository::init(&repo_path).unwrap();
+
+        // `src/a\b.rs` と UTF-8でない `src/c\xff.rs` は表示用のパスではツリーを辿れない
+        let code = repo.blob(b"fn main() {\n    if true {}\n}\n").unwrap();
+        let mut src = repo.treebuilder(None).unwrap();
+        src.insert(b"a\\b.rs".as_slice(), code, 0o100644).unwrap();
+        src.insert(b"c\xff.rs".as_slice(), code, 0o100644).unwrap();
+        let src = src.write().unwrap();
+        let mut root = repo.treebuilder(None).unwrap();
+        root.insert("src", src, 0o040000).unwrap();
+        let tree = repo.find_tree(root.write().unwrap()).unwrap();
+        let signature = Signature::new("Ana", "ana@example.com", &Time::new(100, 0)).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        let db = dir.path().join("history.db");
+        Analyzer::new(Config::new(repo_path.clone(), db.clone()))
+            .analyze()
+            .unwrap();
+
+        let database = Database::open_existing(&db).unwrap();
+        let git_repo = GitRepository::open(&repo_path).unwrap();
+        let hotspots = compute_hotspots(&git_repo, &database, &HotspotOptions::default()).unwrap();
+        let mut paths: Vec<&str> = hotspots.iter().map(|h| h.file_path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, ["src/a\\\\b.rs", "src/c\\xff.rs"]);
+        assert!(hotspots.iter().all(|h| h.functions == 1));
+    }
+}
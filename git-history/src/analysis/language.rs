@@ -0,0 +1,275 @@
+/// 言語の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageKind {
+    /// プログラミング言語
+    Code,
+    /// 設定・データファイル（JSON, YAML, TOML など）
+    Data,
+    /// ドキュメント（Markdown など）
+    Docs,
+    /// 不明
+    Other,
+}
+
+/// 言語ごとの簡易的な解析ルール
+#[derive(Debug)]
+pub struct Language {
+    /// 言語名
+    pub name: &'static str,
+
+    /// 種類
+    pub kind: LanguageKind,
+
+    /// 行コメントの開始記号
+    line_comments: &'static [&'static str],
+
+    /// 関数定義の先頭に現れるキーワード
+    function_keywords: &'static [&'static str],
+
+    /// C系の構文（`name(...) {` を関数定義とみなす）
+    c_like: bool,
+}
+
+const fn code(
+    name: &'static str,
+    line_comments: &'static [&'static str],
+    function_keywords: &'static [&'static str],
+    c_like: bool,
+) -> Language {
+    Language {
+        name,
+        kind: LanguageKind::Code,
+        line_comments,
+        function_keywords,
+        c_like,
+    }
+}
+
+const fn non_code(
+    name: &'static str,
+    kind: LanguageKind,
+    line_comments: &'static [&'static str],
+) -> Language {
+    Language {
+        name,
+        kind,
+        line_comments,
+        function_keywords: &[],
+        c_like: false,
+    }
+}
+
+/// 拡張子と言語の対応表
+static LANGUAGES: &[(&[&str], Language)] = &[
+    (&["rs"], code("Rust", &["//"], &["fn "], false)),
+    (&["py"], code("Python", &["#"], &["def "], false)),
+    (
+        &["js", "jsx", "mjs", "cjs"],
+        code("JavaScript", &["//"], &["function "], false),
+    ),
+    (
+        &["ts", "tsx"],
+        code("TypeScript", &["//"], &["function "], false),
+    ),
+    (&["go"], code("Go", &["//"], &["func "], false)),
+    (&["rb"], code("Ruby", &["#"], &["def "], false)),
+    (
+        &["sh", "bash", "zsh"],
+        code("Shell", &["#"], &["function "], false),
+    ),
+    (&["java"], code("Java", &["//"], &[], true)),
+    (&["kt", "kts"], code("Kotlin", &["//"], &["fun "], false)),
+    (&["swift"], code("Swift", &["//"], &["func "], false)),
+    (&["c", "h"], code("C", &["//"], &[], true)),
+    (
+        &["cc", "cpp", "cxx", "hpp", "hh"],
+        code("C++", &["//"], &[], true),
+    ),
+    (&["cs"], code("C#", &["//"], &[], true)),
+    (&["php"], code("PHP", &["//", "#"], &["function "], false)),
+    (&["scala"], code("Scala", &["//"], &["def "], false)),
+    (
+        &["sql"],
+        code(
+            "SQL",
+            &["--"],
+            &["create function ", "create procedure "],
+            false,
+        ),
+    ),
+    (
+        &["vue", "svelte"],
+        code("Component", &["//"], &["function "], false),
+    ),
+    (&["html", "htm"], non_code("HTML", LanguageKind::Docs, &[])),
+    (
+        &["css", "scss", "sass", "less"],
+        non_code("CSS", LanguageKind::Data, &["//"]),
+    ),
+    (&["json"], non_code("JSON", LanguageKind::Data, &[])),
+    (
+        &["yml", "yaml"],
+        non_code("YAML", LanguageKind::Data, &["#"]),
+    ),
+    (&["toml"], non_code("TOML", LanguageKind::Data, &["#"])),
+    (&["xml"], non_code("XML", LanguageKind::Data, &[])),
+    (&["lock"], non_code("Lockfile", LanguageKind::Data, &[])),
+    (
+        &["md", "markdown", "rst", "txt"],
+        non_code("Text", LanguageKind::Docs, &[]),
+    ),
+];
+
+static OTHER: Language = non_code("Other", LanguageKind::Other, &[]);
+
+/// ファイルパスの拡張子から言語を判定
+pub fn detect_language(file_path: &str) -> &'static Language {
+    let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+    let Some((_, extension)) = file_name.rsplit_once('.') else {
+        return &OTHER;
+    };
+    let extension = extension.to_ascii_lowercase();
+
+    LANGUAGES
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+        .map(|(_, language)| language)
+        .unwrap_or(&OTHER)
+}
+
+/// ファイル内容から求めた複雑度の指標
+#[derive(Debug, Clone, Default)]
+pub struct ComplexityMetrics {
+    /// 総行数
+    pub lines: usize,
+
+    /// 空行・コメント行を除いた行数
+    pub code_lines: usize,
+
+    /// 最大インデント深さ
+    pub max_depth: usize,
+
+    /// コード行の平均インデント深さ
+    pub mean_depth: f64,
+
+    /// 関数定義の数（キーワードによる推定）
+    pub functions: usize,
+}
+
+impl ComplexityMetrics {
+    /// 複雑度スコア（コード行数 × (1 + 平均インデント深さ)）
+    ///
+    /// プログラミング言語以外（設定・ドキュメント）は0とする。
+    pub fn complexity(&self, language: &Language) -> f64 {
+        if language.kind != LanguageKind::Code {
+            return 0.0;
+        }
+        self.code_lines as f64 * (1.0 + self.mean_depth)
+    }
+}
+
+/// ファイル内容をテキストとして扱えるか判定（先頭にNULバイトがあればバイナリ）
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|b| *b == 0)
+}
+
+/// ファイル内容から複雑度の指標を計算
+pub fn measure_complexity(content: &str, language: &Language) -> ComplexityMetrics {
+    let lines: Vec<&str> = content.lines().collect();
+
+    // インデント幅はスペースの最小の字下げ幅から推定（タブは1段）
+    let indent_unit = lines
+        .iter()
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .filter(|width| *width > 0)
+        .min()
+        .unwrap_or(4)
+        .clamp(1, 8);
+
+    let mut metrics = ComplexityMetrics {
+        lines: lines.len(),
+        ..Default::default()
+    };
+    let mut total_depth = 0;
+
+    for line in &lines {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty()
+            || language
+                .line_comments
+                .iter()
+                .any(|prefix| trimmed.starts_with(prefix))
+        {
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        let tabs = indent.chars().filter(|c| *c == '\t').count();
+        let spaces = indent.chars().filter(|c| *c == ' ').count();
+        let depth = tabs + spaces / indent_unit;
+
+        metrics.code_lines += 1;
+        metrics.max_depth = metrics.max_depth.max(depth);
+        total_depth += depth;
+
+        if is_function_definition(trimmed, language) {
+            metrics.functions += 1;
+        }
+    }
+
+    if metrics.code_lines > 0 {
+        metrics.mean_depth = total_depth as f64 / metrics.code_lines as f64;
+    }
+
+    metrics
+}
+
+/// 関数定義の行か判定
+fn is_function_definition(line: &str, language: &Language) -> bool {
+    const MODIFIERS: &[&str] = &[
+        "pub ",
+        "pub(crate) ",
+        "async ",
+        "export ",
+        "default ",
+        "static ",
+        "private ",
+        "public ",
+        "protected ",
+        "internal ",
+        "override ",
+        "const ",
+        "unsafe ",
+    ];
+
+    // 修飾子を取り除く
+    let mut rest = line;
+    while let Some(prefix) = MODIFIERS.iter().find(|m| rest.starts_with(*m)) {
+        rest = &rest[prefix.len()..];
+    }
+
+    let lower = rest.to_ascii_lowercase();
+    if language
+        .function_keywords
+        .iter()
+        .any(|keyword| lower.starts_with(keyword))
+    {
+        return true;
+    }
+
+    if language.c_like {
+        const CONTROL: &[&str] = &[
+            "if", "for", "while", "switch", "catch", "else", "return", "do",
+        ];
+        let first_word = rest
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or("");
+        return rest.ends_with('{')
+            && rest.contains('(')
+            && !rest.contains('=')
+            && !CONTROL.contains(&first_word);
+    }
+
+    false
+}
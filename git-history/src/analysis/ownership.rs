@@ -0,0 +1,344 @@
+use crate::analysis::directory_prefix;
+use crate::database::{ChurnRecord, Database};
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// 所有者分析のオプション
+#[derive(Debug, Clone)]
+pub struct OwnershipOptions {
+    /// 重みの半減期（日数）。古い変更ほど所有度への寄与が小さくなる
+    pub half_life_days: f64,
+
+    /// 最終コミットからこの日数を超えた作成者は非アクティブとみなす
+    pub inactive_days: i64,
+
+    /// ディレクトリ集計の深さ
+    pub depth: usize,
+}
+
+impl Default for OwnershipOptions {
+    fn default() -> Self {
+        Self {
+            half_life_days: 180.0,
+            inactive_days: 90,
+            depth: 2,
+        }
+    }
+}
+
+/// 作成者ごとの所有度
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerShare {
+    pub author_name: String,
+    pub author_email: String,
+
+    /// 重み付きチャーンに占める割合 (0.0〜1.0)
+    pub share: f64,
+
+    /// 非アクティブな作成者かどうか
+    pub inactive: bool,
+}
+
+/// ファイルまたはディレクトリの所有者情報
+#[derive(Debug, Clone, Serialize)]
+pub struct PathOwnership {
+    pub path: String,
+
+    /// 所有度の高い順に並んだ作成者
+    pub owners: Vec<OwnerShare>,
+
+    /// 重み付きチャーンの過半数を占めるのに必要な最小人数
+    pub bus_factor: usize,
+
+    /// 非アクティブな作成者が持つ所有度の合計 (0.0〜1.0)
+    pub knowledge_loss_risk: f64,
+}
+
+impl PathOwnership {
+    /// 主担当者
+    pub fn primary_owner(&self) -> Option<&OwnerShare> {
+        self.owners.first()
+    }
+
+    /// バスファクターに含まれる作成者
+    pub fn key_owners(&self) -> &[OwnerShare] {
+        &self.owners[..self.bus_factor.min(self.owners.len())]
+    }
+}
+
+/// 作成者の活動状況
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorActivity {
+    pub author_name: String,
+    pub author_email: String,
+    pub last_commit_date: i64,
+    pub inactive: bool,
+}
+
+/// 所有者分析の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnershipReport {
+    pub files: Vec<PathOwnership>,
+    pub directories: Vec<PathOwnership>,
+    pub authors: Vec<AuthorActivity>,
+}
+
+/// 所有者分析を実行
+pub fn analyze_ownership(
+    database: &Database,
+    options: &OwnershipOptions,
+) -> Result<OwnershipReport> {
+    let records = database.get_churn_records()?;
+    Ok(compute_ownership(&records, options))
+}
+
+/// チャーン記録から所有者情報を計算
+pub fn compute_ownership(records: &[ChurnRecord], options: &OwnershipOptions) -> OwnershipReport {
+    // 基準時刻は最新のコミット日時（解析時点の履歴を基準にする）
+    let reference_date = records.iter().map(|r| r.commit_date).max().unwrap_or(0);
+
+    // 作成者の最終コミット日時
+    let mut last_commit: HashMap<&str, (&str, i64)> = HashMap::new();
+    for record in records {
+        let entry = last_commit
+            .entry(record.author_email.as_str())
+            .or_insert((record.author_name.as_str(), record.commit_date));
+        if record.commit_date >= entry.1 {
+            *entry = (record.author_name.as_str(), record.commit_date);
+        }
+    }
+
+    let inactive_threshold = reference_date - options.inactive_days * SECONDS_PER_DAY as i64;
+    let is_inactive = |email: &str| {
+        last_commit
+            .get(email)
+            .is_some_and(|(_, date)| *date < inactive_threshold)
+    };
+
+    // 最後の変更が削除（またはリネームによる移動）のファイルは現存しないため除外
+    let mut last_change: HashMap<&str, (i64, bool)> = HashMap::new();
+    for record in records {
+        let renamed_from = record.old_path.as_deref().map(|old_path| (old_path, true));
+        let changed = (record.file_path.as_str(), record.change_type == "DELETE");
+        for (path, deleted) in renamed_from.into_iter().chain([changed]) {
+            let entry = last_change
+                .entry(path)
+                .or_insert((record.commit_date, false));
+            if record.commit_date >= entry.0 {
+                *entry = (record.commit_date, deleted);
+            }
+        }
+    }
+
+    // パスごと・作成者ごとの重み付きチャーン
+    let mut file_scores: HashMap<&str, HashMap<&str, f64>> = HashMap::new();
+    let mut dir_scores: HashMap<String, HashMap<&str, f64>> = HashMap::new();
+
+    for record in records {
+        if last_change
+            .get(record.file_path.as_str())
+            .is_some_and(|(_, deleted)| *deleted)
+        {
+            continue;
+        }
+
+        let age_days = (reference_date - record.commit_date).max(0) as f64 / SECONDS_PER_DAY;
+        let decay = 0.5_f64.powf(age_days / options.half_life_days);
+        let weight = f64::from(record.lines_changed.max(1)) * decay;
+
+        *file_scores
+            .entry(record.file_path.as_str())
+            .or_default()
+            .entry(record.author_email.as_str())
+            .or_default() += weight;
+
+        *dir_scores
+            .entry(directory_prefix(&record.file_path, options.depth))
+            .or_default()
+            .entry(record.author_email.as_str())
+            .or_default() += weight;
+    }
+
+    let build = |path: &str, scores: &HashMap<&str, f64>| {
+        let total: f64 = scores.values().sum();
+
+        let mut owners: Vec<OwnerShare> = scores
+            .iter()
+            .map(|(email, score)| OwnerShare {
+                author_name: last_commit
+                    .get(email)
+                    .map(|(name, _)| name.to_string())
+                    .unwrap_or_default(),
+                author_email: email.to_string(),
+                share: if total > 0.0 { score / total } else { 0.0 },
+                inactive: is_inactive(email),
+            })
+            .collect();
+        owners.sort_by(|a, b| {
+            b.share
+                .total_cmp(&a.share)
+                .then_with(|| a.author_email.cmp(&b.author_email))
+        });
+
+        PathOwnership {
+            path: path.to_string(),
+            bus_factor: bus_factor(&owners),
+            knowledge_loss_risk: owners.iter().filter(|o| o.inactive).map(|o| o.share).sum(),
+            owners,
+        }
+    };
+
+    let mut files: Vec<PathOwnership> = file_scores
+        .iter()
+        .map(|(path, scores)| build(path, scores))
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut directories: Vec<PathOwnership> = dir_scores
+        .iter()
+        .map(|(path, scores)| build(path, scores))
+        .collect();
+    directories.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut authors: Vec<AuthorActivity> = last_commit
+        .iter()
+        .map(|(email, (name, date))| AuthorActivity {
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            last_commit_date: *date,
+            inactive: *date < inactive_threshold,
+        })
+        .collect();
+    authors.sort_by_key(|a| std::cmp::Reverse(a.last_commit_date));
+
+    OwnershipReport {
+        files,
+        directories,
+        authors,
+    }
+}
+
+/// 所有度の過半数を占めるのに必要な最小人数を計算（ownersは所有度の降順）
+fn bus_factor(owners: &[OwnerShare]) -> usize {
+    let mut cumulative = 0.0;
+    for (i, owner) in owners.iter().enumerate() {
+        cumulative += owner.share;
+        if cumulative > 0.5 {
+            return i + 1;
+        }
+    }
+    owners.len()
+}
+
+/// ディレクトリの所有者情報からCODEOWNERSの下書きを生成
+///
+/// 非アクティブな作成者は除外し、バスファクターに含まれる作成者を担当者とする。
+pub fn generate_codeowners(report: &OwnershipReport) -> String {
+    let mut output = String::from(
+        "# CODEOWNERS draft generated by git-history ownership\n\
+         # Owners are derived from recency-weighted churn; review before committing.\n\n",
+    );
+
+    for directory in &report.directories {
+        let owners: Vec<&str> = directory
+            .key_owners()
+            .iter()
+            .filter(|o| !o.inactive)
+            .map(|o| o.author_email.as_str())
+            .collect();
+
+        let pattern = if directory.path == "." {
+            "*".to_string()
+        } else {
+            format!("/{}/", directory.path)
+        };
+
+        if owners.is_empty() {
+            output.push_str(&format!("# {pattern} (no active owner)\n"));
+        } else {
+            output.push_str(&format!("{pattern} {}\n", owners.join(" ")));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn share(email: &str, share: f64) -> OwnerShare {
+        OwnerShare {
+            author_name: email.to_string(),
+            author_email: email.to_string(),
+            share,
+            inactive: false,
+        }
+    }
+
+    fn record(path: &str, change_type: &str, email: &str, day: i64, lines: i32) -> ChurnRecord {
+        ChurnRecord {
+            commit_hash: format!("{email}-{day}"),
+            file_path: path.to_string(),
+            change_type: change_type.to_string(),
+            old_path: None,
+            author_name: email.to_string(),
+            author_email: email.to_string(),
+            commit_date: day * DAY,
+            lines_changed: lines,
+            lines_added: lines,
+            lines_deleted: 0,
+        }
+    }
+
+    #[test]
+    fn bus_factor_counts_owners_until_a_strict_majority() {
+        assert_eq!(bus_factor(&[share("a", 0.6), share("b", 0.4)]), 1);
+        // ちょうど半分は過半数ではない
+        assert_eq!(bus_factor(&[share("a", 0.5), share("b", 0.5)]), 2);
+        assert_eq!(
+            bus_factor(&[
+                share("a", 0.3),
+                share("b", 0.3),
+                share("c", 0.2),
+                share("d", 0.2)
+            ]),
+            2
+        );
+        assert_eq!(bus_factor(&[]), 0);
+    }
+
+    #[test]
+    fn ownership_weights_recent_churn_and_skips_deleted_files() {
+        let records = [
+            record("src/a.rs", "ADD", "old@example.com", 0, 100),
+            record("src/a.rs", "MODIFY", "new@example.com", 360, 30),
+            record("src/gone.rs", "ADD", "new@example.com", 10, 10),
+            record("src/gone.rs", "DELETE", "new@example.com", 20, 10),
+        ];
+        let options = OwnershipOptions {
+            half_life_days: 180.0,
+            inactive_days: 90,
+            depth: 1,
+        };
+
+        let report = compute_ownership(&records, &options);
+
+        // 100行は2半減期を経て25相当になり、最近の30行が上回る
+        assert_eq!(report.files.len(), 1);
+        let file = &report.files[0];
+        assert_eq!(file.path, "src/a.rs");
+        assert_eq!(
+            file.primary_owner().unwrap().author_email,
+            "new@example.com"
+        );
+        assert_eq!(file.bus_factor, 1);
+        assert!((file.knowledge_loss_risk - 25.0 / 55.0).abs() < 1e-9);
+        assert_eq!(report.directories[0].path, "src");
+    }
+}
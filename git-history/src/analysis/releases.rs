@@ -0,0 +1,247 @@
+use crate::database::{Database, ReleaseInfo, ReleaseStats};
+use crate::error::Result;
+use crate::git::GitRepository;
+use chrono::DateTime;
+use git2::Oid;
+use serde::Serialize;
+
+/// Conventional Commitsの種別と見出し（changelogの表示順）
+const CHANGELOG_SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("style", "Style"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+    ("other", "Other Changes"),
+];
+
+/// changelogの1行
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogEntry {
+    /// 種別（feat, fix, ... 該当しない場合は"other"）
+    pub kind: String,
+
+    /// スコープ（`feat(parser): ...` の `parser`）
+    pub scope: Option<String>,
+
+    /// 説明
+    pub description: String,
+
+    /// 破壊的変更（`feat!: ...`）
+    pub breaking: bool,
+
+    /// コミットハッシュ
+    pub commit_hash: String,
+}
+
+/// リリースごとの分析結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseReport {
+    pub release: ReleaseInfo,
+
+    /// 直前のリリースのタグ名（最初のリリースはNone）
+    pub previous_tag: Option<String>,
+
+    pub stats: ReleaseStats,
+
+    pub changelog: Vec<ChangelogEntry>,
+}
+
+/// タグを日時順に並べ、直前のリリースとの差分を分析して releases テーブルに保存
+pub fn analyze_releases(
+    git_repo: &GitRepository,
+    database: &mut Database,
+) -> Result<Vec<ReleaseReport>> {
+    let repo = git_repo.inner();
+
+    let mut tags = git_repo.get_tags()?;
+    tags.sort_by(|a, b| {
+        a.tag_date
+            .cmp(&b.tag_date)
+            .then_with(|| a.tag_name.cmp(&b.tag_name))
+    });
+
+    let mut releases = Vec::new();
+    let mut changelogs = Vec::new();
+    let mut previous: Option<&ReleaseInfo> = None;
+
+    for tag in &tags {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(Oid::from_str(&tag.target_commit)?)?;
+        if let Some(prev) = previous {
+            revwalk.hide(Oid::from_str(&prev.target_commit)?)?;
+        }
+
+        let mut commit_hashes = Vec::new();
+        let mut changelog = Vec::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            commit_hashes.push(commit.id().to_string());
+
+            // マージコミットはchangelogに含めない
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            if let Some(subject) = commit.summary() {
+                changelog.push(parse_conventional_commit(subject, &commit.id().to_string()));
+            }
+        }
+
+        releases.push((
+            tag.clone(),
+            previous.map(|p| p.tag_name.clone()),
+            commit_hashes,
+        ));
+        changelogs.push(changelog);
+        previous = Some(tag);
+    }
+
+    database.replace_releases(&releases)?;
+    let mut stats = database.get_release_stats()?;
+
+    let reports = releases
+        .into_iter()
+        .zip(changelogs)
+        .map(|((release, previous_tag, _), changelog)| ReleaseReport {
+            stats: stats.remove(&release.tag_name).unwrap_or_default(),
+            release,
+            previous_tag,
+            changelog,
+        })
+        .collect();
+
+    Ok(reports)
+}
+
+/// コミットの件名をConventional Commits形式として解析
+///
+/// `type(scope)!: description` に一致しない場合は種別を"other"とする。
+pub fn parse_conventional_commit(subject: &str, commit_hash: &str) -> ChangelogEntry {
+    let other = || ChangelogEntry {
+        kind: "other".to_string(),
+        scope: None,
+        description: subject.trim().to_string(),
+        breaking: false,
+        commit_hash: commit_hash.to_string(),
+    };
+
+    let Some((prefix, description)) = subject.split_once(':') else {
+        return other();
+    };
+
+    let (prefix, breaking) = match prefix.strip_suffix('!') {
+        Some(rest) => (rest, true),
+        None => (prefix, false),
+    };
+
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, rest)) => match rest.strip_suffix(')') {
+            Some(scope) => (kind, Some(scope.to_string())),
+            None => return other(),
+        },
+        None => (prefix, None),
+    };
+
+    let kind = kind.to_ascii_lowercase();
+    if !CHANGELOG_SECTIONS
+        .iter()
+        .any(|(section, _)| *section == kind && kind != "other")
+    {
+        return other();
+    }
+
+    ChangelogEntry {
+        kind,
+        scope,
+        description: description.trim().to_string(),
+        breaking,
+        commit_hash: commit_hash.to_string(),
+    }
+}
+
+/// リリースごとのchangelogをMarkdownで生成（新しいリリースが先頭）
+pub fn generate_changelog(reports: &[ReleaseReport]) -> String {
+    let mut output = String::from("# Changelog\n");
+
+    for report in reports.iter().rev() {
+        let date = DateTime::from_timestamp(report.release.tag_date, 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        output.push_str(&format!("\n## {} ({})\n", report.release.tag_name, date));
+
+        for (kind, heading) in CHANGELOG_SECTIONS {
+            let entries: Vec<&ChangelogEntry> = report
+                .changelog
+                .iter()
+                .filter(|e| e.kind == *kind)
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+
+            output.push_str(&format!("\n### {}\n\n", heading));
+            for entry in entries {
+                let breaking = if entry.breaking { "**BREAKING** " } else { "" };
+                let scope = entry
+                    .scope
+                    .as_ref()
+                    .map(|s| format!("**{}:** ", s))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "- {}{}{} ({})\n",
+                    breaking,
+                    scope,
+                    entry.description,
+                    &entry.commit_hash[..7]
+                ));
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conventional_commits_are_split_into_type_scope_and_description() {
+        let entry = parse_conventional_commit("feat(parser): accept tabs", "abc");
+        assert_eq!(entry.kind, "feat");
+        assert_eq!(entry.scope.as_deref(), Some("parser"));
+        assert_eq!(entry.description, "accept tabs");
+        assert!(!entry.breaking);
+        assert_eq!(entry.commit_hash, "abc");
+
+        let entry = parse_conventional_commit("Fix!: drop the v1 API", "abc");
+        assert_eq!(entry.kind, "fix");
+        assert_eq!(entry.scope, None);
+        assert!(entry.breaking);
+
+        let entry = parse_conventional_commit("refactor(db)!: rename tables", "abc");
+        assert_eq!(entry.scope.as_deref(), Some("db"));
+        assert!(entry.breaking);
+    }
+
+    #[test]
+    fn other_subjects_keep_their_full_text() {
+        for subject in [
+            "Update README",
+            "wip: not a known type",
+            "feat(parser: missing paren",
+            "other: reserved section",
+        ] {
+            let entry = parse_conventional_commit(subject, "abc");
+            assert_eq!(entry.kind, "other", "{subject}");
+            assert_eq!(entry.description, subject);
+            assert!(!entry.breaking);
+        }
+    }
+}
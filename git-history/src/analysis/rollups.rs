@@ -0,0 +1,75 @@
+use crate::analysis::timeline::Timezone;
+use crate::database::Database;
+use crate::error::{GitHistoryError, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// 集計の時間単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    Day,
+    /// ISO週（月曜始まり）
+    Week,
+    Month,
+}
+
+impl TimeBucket {
+    /// 文字列に変換（DuckDBの date_trunc の単位と同じ）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeBucket::Day => "day",
+            TimeBucket::Week => "week",
+            TimeBucket::Month => "month",
+        }
+    }
+}
+
+impl fmt::Display for TimeBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TimeBucket {
+    type Err = GitHistoryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "day" | "daily" => Ok(TimeBucket::Day),
+            "week" | "weekly" => Ok(TimeBucket::Week),
+            "month" | "monthly" => Ok(TimeBucket::Month),
+            _ => Err(GitHistoryError::ConfigError(format!(
+                "Unknown time bucket: {} (expected day, week or month)",
+                s
+            ))),
+        }
+    }
+}
+
+/// ディレクトリ集計のオプション
+#[derive(Debug, Clone, Copy)]
+pub struct RollupOptions {
+    /// 集計するディレクトリの最大深さ（1〜depthの各深さで集計）
+    pub depth: usize,
+
+    /// 時間単位
+    pub bucket: TimeBucket,
+}
+
+impl Default for RollupOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            bucket: TimeBucket::Month,
+        }
+    }
+}
+
+/// dir_rollups テーブルを再計算（期間は activity_timeline と同じタイムゾーンで区切る）
+pub fn refresh_directory_rollups(
+    database: &mut Database,
+    options: &RollupOptions,
+    timezone: Timezone,
+) -> Result<()> {
+    database.refresh_dir_rollups(options.depth, options.bucket, timezone)
+}
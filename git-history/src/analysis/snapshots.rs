@@ -0,0 +1,179 @@
+use crate::analysis::{directory_prefix, language};
+use crate::database::{Database, TreeSnapshot};
+use crate::error::{GitHistoryError, Result};
+use crate::git::{GitRepository, tree};
+use chrono::DateTime;
+use git2::Oid;
+use std::collections::{BTreeMap, HashMap};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// スナップショットのサンプリング設定
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    /// 起点となるリビジョン（first-parentで遡る）
+    pub revision: String,
+
+    /// タグが指すコミットをサンプリングする
+    pub tags: bool,
+
+    /// N日ごとにサンプリングする
+    pub every_days: Option<u32>,
+
+    /// ディレクトリ集計の深さ
+    pub depth: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            revision: "HEAD".to_string(),
+            tags: true,
+            every_days: Some(30),
+            depth: 1,
+        }
+    }
+}
+
+/// サンプリングしたコミット
+struct SampledCommit {
+    oid: Oid,
+    date: i64,
+    label: String,
+}
+
+/// サンプリングしたコミットのツリーを走査して tree_snapshots テーブルに保存
+///
+/// チェックアウトは行わず、同じblobの行数は一度だけ数える。
+pub fn take_snapshots(
+    git_repo: &GitRepository,
+    database: &mut Database,
+    options: &SnapshotOptions,
+) -> Result<Vec<TreeSnapshot>> {
+    let samples = sample_commits(git_repo, options)?;
+
+    // blob OID → 行数（バイナリは0）
+    let mut line_cache: HashMap<Oid, i64> = HashMap::new();
+    let mut snapshots = Vec::new();
+
+    for sample in &samples {
+        let commit = git_repo.inner().find_commit(sample.oid)?;
+        let commit_tree = commit.tree()?;
+
+        let mut files = Vec::new();
+        tree::walk_blobs(git_repo.inner(), &commit_tree, |path, oid| {
+            files.push((path.to_string(), oid))
+        })?;
+
+        // 集計軸ごとの (ファイル数, 行数)
+        let mut totals: BTreeMap<(&str, String), (i64, i64)> = BTreeMap::new();
+        for (path, oid) in &files {
+            let lines = match line_cache.get(oid) {
+                Some(lines) => *lines,
+                None => {
+                    let blob = git_repo.inner().find_blob(*oid)?;
+                    let content = blob.content();
+                    let lines = if language::is_binary(content) {
+                        0
+                    } else {
+                        let newlines = content.iter().filter(|b| **b == b'\n').count();
+                        let unterminated = content.last().is_some_and(|b| *b != b'\n');
+                        (newlines + usize::from(unterminated)) as i64
+                    };
+                    line_cache.insert(*oid, lines);
+                    lines
+                }
+            };
+
+            let keys = [
+                ("total", "*".to_string()),
+                ("language", language::detect_language(path).name.to_string()),
+                ("directory", directory_prefix(path, options.depth)),
+            ];
+            for key in keys {
+                let entry = totals.entry(key).or_default();
+                entry.0 += 1;
+                entry.1 += lines;
+            }
+        }
+
+        snapshots.extend(
+            totals
+                .into_iter()
+                .map(|((dimension, key), (files, lines))| TreeSnapshot {
+                    commit_hash: sample.oid.to_string(),
+                    snapshot_date: sample.date,
+                    label: sample.label.clone(),
+                    dimension: dimension.to_string(),
+                    key,
+                    files,
+                    lines,
+                }),
+        );
+    }
+
+    database.insert_tree_snapshots(&snapshots)?;
+    Ok(snapshots)
+}
+
+/// サンプリング対象のコミットを選ぶ（古い順）
+fn sample_commits(
+    git_repo: &GitRepository,
+    options: &SnapshotOptions,
+) -> Result<Vec<SampledCommit>> {
+    if !options.tags && options.every_days.is_none() {
+        return Err(GitHistoryError::ConfigError(
+            "Snapshots need at least one sampling method (tags or every N days)".to_string(),
+        ));
+    }
+
+    let repo = git_repo.inner();
+    let mut samples: BTreeMap<Oid, SampledCommit> = BTreeMap::new();
+
+    if options.tags {
+        for tag in git_repo.get_tags()? {
+            let oid = Oid::from_str(&tag.target_commit)?;
+            let date = repo.find_commit(oid)?.time().seconds();
+            samples.insert(
+                oid,
+                SampledCommit {
+                    oid,
+                    date,
+                    label: tag.tag_name,
+                },
+            );
+        }
+    }
+
+    if let Some(every_days) = options.every_days {
+        let interval = i64::from(every_days.max(1)) * SECONDS_PER_DAY;
+        let start = repo.revparse_single(&options.revision)?.peel_to_commit()?;
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(start.id())?;
+        revwalk.simplify_first_parent()?;
+
+        // 新しい方から遡り、前回のサンプルから interval 以上離れたコミットを選ぶ
+        let mut next_threshold = i64::MAX;
+        for oid in revwalk {
+            let oid = oid?;
+            let date = repo.find_commit(oid)?.time().seconds();
+            if date > next_threshold {
+                continue;
+            }
+            next_threshold = date - interval;
+
+            samples.entry(oid).or_insert_with(|| SampledCommit {
+                oid,
+                date,
+                label: DateTime::from_timestamp(date, 0)
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    let mut samples: Vec<SampledCommit> = samples.into_values().collect();
+    samples.sort_by_key(|s| s.date);
+    Ok(samples)
+}
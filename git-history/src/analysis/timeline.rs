@@ -0,0 +1,216 @@
+use crate::analysis::rollups::TimeBucket;
+use crate::database::{ActivityBucket, CommitActivity, Database};
+use crate::error::{GitHistoryError, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// 期間の区切りに使うタイムゾーン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timezone {
+    /// UTC
+    Utc,
+    /// 各コミットに記録されたタイムゾーン（作成者のローカル日付）
+    Author,
+    /// 固定オフセット（分）。夏時間の切り替えは考慮しないので、必要なら `Author` を使う
+    Fixed(i32),
+}
+
+impl Timezone {
+    /// コミット日時をこのタイムゾーンでの日付に変換
+    pub fn local_date(&self, commit_date: i64, commit_tz_offset: i32) -> NaiveDate {
+        let offset_minutes = match self {
+            Timezone::Utc => 0,
+            Timezone::Author => commit_tz_offset,
+            Timezone::Fixed(minutes) => *minutes,
+        };
+
+        DateTime::from_timestamp(commit_date + i64::from(offset_minutes) * 60, 0)
+            .unwrap_or_default()
+            .date_naive()
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Utc => write!(f, "utc"),
+            Timezone::Author => write!(f, "author"),
+            Timezone::Fixed(minutes) => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                write!(
+                    f,
+                    "{}{:02}:{:02}",
+                    sign,
+                    minutes.abs() / 60,
+                    minutes.abs() % 60
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = GitHistoryError;
+
+    /// "utc", "author", "+09:00", "-0530" の形式を受け付ける
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            GitHistoryError::ConfigError(format!(
+                "Invalid timezone: {} (expected utc, author or a fixed offset like +09:00; zone names are not supported)",
+                s
+            ))
+        };
+
+        match s.to_ascii_lowercase().as_str() {
+            "utc" | "z" => return Ok(Timezone::Utc),
+            "author" | "local" => return Ok(Timezone::Author),
+            _ => {}
+        }
+
+        let (sign, rest) = match s.chars().next() {
+            Some('+') => (1, &s[1..]),
+            Some('-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let digits: String = rest.chars().filter(|c| *c != ':').collect();
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let hours: i32 = digits[..2].parse().map_err(|_| invalid())?;
+        let minutes: i32 = digits[2..].parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+
+        Ok(Timezone::Fixed(sign * (hours * 60 + minutes)))
+    }
+}
+
+/// 日付を期間の開始日に切り捨て（週は月曜始まり）
+pub fn bucket_start(date: NaiveDate, bucket: TimeBucket) -> NaiveDate {
+    match bucket {
+        TimeBucket::Day => date,
+        TimeBucket::Week => date - Duration::days(i64::from(date.weekday().num_days_from_monday())),
+        TimeBucket::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+/// コミットごとの活動量を期間ごとに集計
+pub fn compute_activity(
+    activity: &[CommitActivity],
+    bucket: TimeBucket,
+    timezone: Timezone,
+) -> Vec<ActivityBucket> {
+    #[derive(Default)]
+    struct Accumulator<'a> {
+        commits: i64,
+        authors: HashSet<&'a str>,
+        lines_added: i64,
+        lines_deleted: i64,
+        new_files: i64,
+    }
+
+    let mut buckets: BTreeMap<NaiveDate, Accumulator> = BTreeMap::new();
+    for commit in activity {
+        let date = timezone.local_date(commit.commit_date, commit.commit_tz_offset);
+        let acc = buckets.entry(bucket_start(date, bucket)).or_default();
+
+        acc.commits += 1;
+        acc.authors.insert(commit.author_email.as_str());
+        acc.lines_added += commit.lines_added;
+        acc.lines_deleted += commit.lines_deleted;
+        acc.new_files += commit.new_files;
+    }
+
+    buckets
+        .into_iter()
+        .map(|(start, acc)| ActivityBucket {
+            bucket: bucket.as_str().to_string(),
+            bucket_start: start.format("%Y-%m-%d").to_string(),
+            timezone: timezone.to_string(),
+            commits: acc.commits,
+            active_authors: acc.authors.len() as i64,
+            lines_added: acc.lines_added,
+            lines_deleted: acc.lines_deleted,
+            new_files: acc.new_files,
+        })
+        .collect()
+}
+
+/// activity_timeline テーブルを日・週・月の全単位で再計算
+pub fn refresh_activity_timeline(database: &mut Database, timezone: Timezone) -> Result<()> {
+    let activity = database.get_commit_activity()?;
+
+    let buckets: Vec<ActivityBucket> = [TimeBucket::Day, TimeBucket::Week, TimeBucket::Month]
+        .into_iter()
+        .flat_map(|bucket| compute_activity(&activity, bucket, timezone))
+        .collect();
+
+    database.replace_activity_timeline(&buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(email: &str, commit_date: i64, commit_tz_offset: i32) -> CommitActivity {
+        CommitActivity {
+            author_email: email.to_string(),
+            commit_date,
+            commit_tz_offset,
+            lines_added: 1,
+            lines_deleted: 0,
+            new_files: 0,
+        }
+    }
+
+    #[test]
+    fn timezone_parses_names_and_offsets() {
+        assert_eq!("UTC".parse::<Timezone>().unwrap(), Timezone::Utc);
+        assert_eq!("z".parse::<Timezone>().unwrap(), Timezone::Utc);
+        assert_eq!("author".parse::<Timezone>().unwrap(), Timezone::Author);
+        assert_eq!("+09:00".parse::<Timezone>().unwrap(), Timezone::Fixed(540));
+        assert_eq!("-0530".parse::<Timezone>().unwrap(), Timezone::Fixed(-330));
+        assert_eq!("+14:00".parse::<Timezone>().unwrap(), Timezone::Fixed(840));
+
+        for invalid in ["", "09:00", "+9", "+15:00", "+09:60", "+0a:00", "jst"] {
+            assert!(invalid.parse::<Timezone>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn timezone_round_trips_through_display() {
+        for timezone in [Timezone::Utc, Timezone::Author, Timezone::Fixed(-330)] {
+            assert_eq!(timezone.to_string().parse::<Timezone>().unwrap(), timezone);
+        }
+        assert_eq!(Timezone::Fixed(540).to_string(), "+09:00");
+    }
+
+    #[test]
+    fn activity_is_bucketed_in_the_requested_timezone() {
+        // 2024-01-07（日）23:30 UTC は +09:00 では 2024-01-08（月）
+        let sunday_night = 1_704_670_200;
+        let commits = [
+            activity("a@example.com", sunday_night, 0),
+            activity("b@example.com", sunday_night - 86_400, 540),
+        ];
+
+        let utc = compute_activity(&commits, TimeBucket::Week, Timezone::Utc);
+        assert_eq!(utc.len(), 1);
+        assert_eq!(utc[0].bucket_start, "2024-01-01");
+        assert_eq!(utc[0].commits, 2);
+        assert_eq!(utc[0].active_authors, 2);
+
+        let tokyo = compute_activity(&commits, TimeBucket::Week, Timezone::Fixed(540));
+        let starts: Vec<&str> = tokyo.iter().map(|b| b.bucket_start.as_str()).collect();
+        assert_eq!(starts, ["2024-01-01", "2024-01-08"]);
+
+        // 作成者のタイムゾーンでは b の土曜 23:30 UTC が +09:00 の日曜になる
+        let author = compute_activity(&commits, TimeBucket::Day, Timezone::Author);
+        let starts: Vec<&str> = author.iter().map(|b| b.bucket_start.as_str()).collect();
+        assert_eq!(starts, ["2024-01-07"]);
+    }
+}
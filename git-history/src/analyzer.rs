@@ -1,132 +1,665 @@
+use crate::analysis::{commit_kinds, rollups, timeline};
+use crate::cancellation::CancellationToken;
 use crate::config::Config;
-use crate::database::Database;
-use crate::error::Result;
-use crate::git::{diff, GitRepository};
+use crate::database::{
+    AnalysisCheckpoint, AnalysisErrorRecord, AnalysisRun, CommitInfo, Database, FileChange,
+    RunStatus,
+};
+use crate::error::{GitHistoryError, Result};
+use crate::git::{CommitSelection, GitRepository, RepositoryKind, diff, remote};
+use crate::progress::{CommitProgress, NoProgress, Phase, ProgressObserver};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 /// 分析結果
 #[derive(Debug)]
 pub struct AnalysisResult {
+    /// analysis_runs に記録した実行ID
+    pub run_id: i64,
     pub total_commits: usize,
     pub total_files: usize,
     pub processing_time: Duration,
+    /// 今回の実行で処理したコミット数
+    pub processed_commits: usize,
+    /// 中断した前回の実行で保存済みだったため再開時にスキップしたコミット数
+    pub resumed_commits: usize,
+    /// 解析に失敗して analysis_errors に記録したコミット数（`--keep-going`）
+    pub failed_commits: usize,
+    /// 解析したリポジトリのパス（クローンURLの場合はミラーのパス）
+    pub repository_path: PathBuf,
+    /// 解析したリポジトリの種類
+    pub repository_kind: RepositoryKind,
+    /// 今回新たに孤立としてマークしたコミット数
+    pub orphaned_commits: usize,
+    /// 削除した孤立コミット数
+    pub pruned_commits: usize,
+    /// commit_kinds に分類した（レポートから除外する）コミット数
+    pub flagged_commits: usize,
+    /// shallow clone の境界のため変更を記録しなかったコミット数
+    pub grafted_commits: usize,
+}
+
+/// 1バッチ分の抽出結果
+#[derive(Default)]
+struct CommitBatch {
+    commits: Vec<CommitInfo>,
+    file_changes: Vec<FileChange>,
+    errors: Vec<AnalysisErrorRecord>,
+    /// 変更を記録しなかった shallow clone の境界のコミット数
+    grafted_commits: usize,
 }
 
 /// アナライザー（メインのワークフロー制御）
 pub struct Analyzer {
     config: Config,
+    progress: Box<dyn ProgressObserver>,
+    cancellation: CancellationToken,
 }
 
 impl Analyzer {
     /// 新しいAnalyzerを作成
     pub fn new(config: Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            progress: Box::new(NoProgress),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// 中断要求を受け取るトークンを設定
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// 進捗の通知先を設定
+    pub fn with_progress(mut self, progress: impl ProgressObserver + 'static) -> Self {
+        self.progress = Box::new(progress);
+        self
     }
 
     /// 分析を実行
     pub fn analyze(&self) -> Result<AnalysisResult> {
         let start_time = Instant::now();
 
-        println!("🔍 Analyzing repository: {}", self.config.repo_path.display());
+        // クローンURLの場合はミラーキャッシュを作成・更新して解析
+        let repo_path = match &self.config.remote_url {
+            Some(url) => self.run_phase(Phase::SyncingMirror, || {
+                remote::sync_mirror(url, &self.config.mirror)
+            })?,
+            None => self.config.repo_path.clone(),
+        };
+
+        // Gitリポジトリとデータベースを開く
+        let (git_repo, mut database) = self.run_phase(Phase::Opening, || {
+            let git_repo = GitRepository::open(&repo_path)?;
+            let database = Database::new(&self.config.output_db)?;
+            database.create_tables()?;
+            check_rename_detection(&database, self.config.diff.detect_renames)?;
+            Ok((git_repo, database))
+        })?;
+
+        // 実行履歴を記録（失敗・中断した場合も結果を残す）
+        let selection = self.config.commit_selection();
+        let mut run = self.new_run(&git_repo, &selection);
+        run.run_id = database.start_analysis_run(&run)?;
+
+        let outcome = self.analyze_commits(&git_repo, &mut database, run.run_id, &selection);
+
+        run.finished_at = Some(Utc::now().timestamp());
+        match &outcome {
+            Ok(result) => {
+                run.status = RunStatus::Completed;
+                run.selected_commits =
+                    Some((result.processed_commits + result.resumed_commits) as i64);
+                run.processed_commits = Some(result.processed_commits as i64);
+                run.resumed_commits = Some(result.resumed_commits as i64);
+                run.failed_commits = Some(result.failed_commits as i64);
+                run.orphaned_commits = Some(result.orphaned_commits as i64);
+            }
+            Err(e) => {
+                run.status = match e {
+                    GitHistoryError::Cancelled { .. } => RunStatus::Cancelled,
+                    _ => RunStatus::Failed,
+                };
+                if let GitHistoryError::Cancelled { total, .. } = e {
+                    run.selected_commits = Some(*total as i64);
+                }
+                run.error = Some(e.to_string());
+            }
+        }
+        // 解析のエラーを優先して返す（記録の失敗で元のエラーを隠さない）
+        let recorded = database.finish_analysis_run(&run);
+        let mut result = outcome?;
+        recorded?;
 
-        // Gitリポジトリを開く
-        let git_repo = GitRepository::open(&self.config.repo_path)?;
-        println!("✓ Repository opened successfully");
+        result.processing_time = start_time.elapsed();
+        Ok(result)
+    }
 
-        // データベースを初期化
-        let mut database = Database::new(&self.config.output_db)?;
-        database.create_tables()?;
-        println!("✓ Database initialized: {}", self.config.output_db.display());
+    /// 開始時点の実行履歴を作成
+    fn new_run(&self, git_repo: &GitRepository, selection: &CommitSelection) -> AnalysisRun {
+        // 参照を解決できない場合はこの後のコミット一覧の取得でエラーになり、FAILEDとして記録される
+        let head_oids: Vec<String> = git_repo
+            .analyzed_tips(selection)
+            .unwrap_or_default()
+            .iter()
+            .map(|oid| oid.to_string())
+            .collect();
+        let refs = selection
+            .range
+            .clone()
+            .or_else(|| selection.branch.clone())
+            .unwrap_or_else(|| "HEAD".to_string());
 
+        AnalysisRun {
+            run_id: 0,
+            started_at: Utc::now().timestamp(),
+            finished_at: None,
+            status: RunStatus::Running,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            repo_path: git_repo.root().display().to_string(),
+            remote_url: self.config.remote_url.clone(),
+            refs,
+            options: run_options(&self.config),
+            head_oids: serde_json::to_string(&head_oids).unwrap_or_default(),
+            selected_commits: None,
+            processed_commits: None,
+            resumed_commits: None,
+            failed_commits: None,
+            orphaned_commits: None,
+            error: None,
+        }
+    }
+
+    /// コミットを解析して保存し、集計を更新
+    fn analyze_commits(
+        &self,
+        git_repo: &GitRepository,
+        database: &mut Database,
+        run_id: i64,
+        selection: &CommitSelection,
+    ) -> Result<AnalysisResult> {
         // コミット一覧を取得
-        let commit_oids = git_repo.get_commits(
-            self.config.branch.as_deref(),
-            self.config.limit,
-        )?;
-        println!("✓ Found {} commits", commit_oids.len());
+        let commit_oids =
+            self.run_phase(Phase::CollectingCommits, || git_repo.get_commits(selection))?;
+
+        // 同じ解析対象の中断した実行があれば、保存済みのコミットの次から再開
+        let selection_key = format!("{}|{:?}", git_repo.root().display(), selection);
+        let checkpoint = if self.config.resume {
+            database
+                .get_checkpoint(&selection_key)?
+                .filter(|checkpoint| checkpoint_matches(checkpoint, &commit_oids))
+        } else {
+            None
+        };
+        let resumed_commits = checkpoint
+            .as_ref()
+            .map_or(0, |checkpoint| checkpoint.processed_commits as usize);
+        if resumed_commits > 0 {
+            self.progress
+                .resumed_from_checkpoint(resumed_commits, commit_oids.len());
+        }
+
+        // 並び順に関係なく累積カラムを計算できるようにトポロジカル順位を付与
+        // （再開時は中断前と同じ順位になるよう基準値を引き継ぐ）
+        let base_rank = match &checkpoint {
+            Some(checkpoint) => checkpoint.base_topo_order,
+            None => database.get_max_topo_order()? + 1,
+        };
+        let topo_ranks: HashMap<git2::Oid, i64> = git_repo
+            .topological_order(&commit_oids)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, oid)| (oid, base_rank + i as i64))
+            .collect();
 
         // コミットを処理
-        let batch_size = 100;
-        let total = commit_oids.len();
+        let mut failed_commits = 0;
+        let mut grafted_commits = 0;
+        self.run_phase(Phase::ProcessingCommits, || {
+            let batch_size = 100;
+            let remaining = &commit_oids[resumed_commits..];
+            let total = remaining.len();
+            let processing_start = Instant::now();
+
+            self.progress.commits_processed(&CommitProgress {
+                processed: 0,
+                total,
+                elapsed: Duration::ZERO,
+            });
 
-        for (batch_idx, chunk) in commit_oids.chunks(batch_size).enumerate() {
-            let start = batch_idx * batch_size;
-            let end = (start + chunk.len()).min(total);
+            let mut processed = 0;
+            for chunk in remaining.chunks(batch_size) {
+                // 中断要求はバッチの区切りでのみ確認（保存済みのバッチまではチェックポイントに記録済み）
+                if self.cancellation.is_cancelled() {
+                    return Err(GitHistoryError::Cancelled {
+                        processed: resumed_commits + processed,
+                        total: commit_oids.len(),
+                    });
+                }
 
-            if self.config.verbose {
-                println!("  Processing commits {}-{}/{}", start + 1, end, total);
-            } else {
-                print!("\r  Processing: {}/{} commits", end, total);
+                let batch = self.extract_commit_batch(git_repo, chunk, &topo_ranks)?;
+                failed_commits += batch.errors.len();
+                grafted_commits += batch.grafted_commits;
+
+                processed += chunk.len();
+                let checkpoint = AnalysisCheckpoint {
+                    selection_key: selection_key.clone(),
+                    total_commits: commit_oids.len() as i64,
+                    processed_commits: (resumed_commits + processed) as i64,
+                    last_commit_hash: chunk[chunk.len() - 1].to_string(),
+                    base_topo_order: base_rank,
+                };
+                database.insert_commit_batch(
+                    run_id,
+                    &batch.commits,
+                    &batch.file_changes,
+                    &batch.errors,
+                    &checkpoint,
+                )?;
+
+                self.progress.commits_processed(&CommitProgress {
+                    processed,
+                    total,
+                    elapsed: processing_start.elapsed(),
+                });
             }
+            Ok(())
+        })?;
 
-            self.process_commit_batch(&git_repo, &mut database, chunk)?;
-        }
+        // 履歴の書き換えで到達不能になったコミットを検出
+        let (orphaned_commits, pruned_commits) = if self.config.incremental {
+            self.run_phase(Phase::DetectingOrphans, || {
+                self.detect_orphaned_commits(git_repo, database)
+            })?
+        } else {
+            (0, 0)
+        };
 
-        println!("\n✓ All commits processed");
+        // 過去の実行で記録したコミットも含めてトポロジカル順位を付け直し、
+        // 累積コミット回数をトポロジカル順で計算
+        self.run_phase(Phase::ComputingCommitCounts, || {
+            database.recompute_topo_order()?;
+            database.recompute_commit_counts()
+        })?;
+
+        // 一括変更・整形・リネームのみのコミットを分類
+        let flagged_commits = self.run_phase(Phase::ClassifyingCommits, || {
+            commit_kinds::refresh_commit_kinds(database, &self.config.commit_kinds)
+        })?;
+
+        // ディレクトリ集計を更新
+        self.run_phase(Phase::RefreshingRollups, || {
+            rollups::refresh_directory_rollups(database, &self.config.rollups, self.config.timezone)
+        })?;
+
+        // 時系列集計を更新
+        self.run_phase(Phase::RefreshingTimeline, || {
+            timeline::refresh_activity_timeline(database, self.config.timezone)
+        })?;
+
+        // 全工程が完了したのでチェックポイントは不要
+        database.clear_checkpoint(&selection_key)?;
 
         // 統計情報を取得
         let total_commits = database.get_total_commits()?;
         let total_files = database.get_total_files()?;
 
-        let processing_time = start_time.elapsed();
-
-        println!("\n📊 Analysis complete!");
-        println!("  Total commits: {}", total_commits);
-        println!("  Total files: {}", total_files);
-        println!("  Processing time: {:.2}s", processing_time.as_secs_f64());
-        println!("  Database: {}", self.config.output_db.display());
-
         Ok(AnalysisResult {
+            run_id,
             total_commits,
             total_files,
-            processing_time,
+            processing_time: Duration::ZERO,
+            processed_commits: commit_oids.len() - resumed_commits,
+            resumed_commits,
+            failed_commits,
+            repository_path: git_repo.root().to_path_buf(),
+            repository_kind: git_repo.kind(),
+            orphaned_commits,
+            pruned_commits,
+            flagged_commits,
+            grafted_commits,
         })
     }
 
-    /// コミットバッチを処理
-    fn process_commit_batch(
+    /// 工程を実行し、開始と完了をオブザーバーに通知
+    fn run_phase<T>(&self, phase: Phase, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        self.progress.phase_started(phase);
+        let value = f()?;
+        self.progress.phase_finished(phase, start.elapsed());
+        Ok(value)
+    }
+
+    /// 解析済みのどの参照からも到達できなくなったコミットを孤立としてマーク（必要なら削除）
+    ///
+    /// 今回の参照だけで判定すると、別のブランチを解析したときにそれまでのブランチのコミットが
+    /// 孤立扱いになるため、analysis_runs に記録したすべての参照を現在のコミットに解決し直す。
+    /// 削除されて解決できなくなった参照は無視する。
+    ///
+    /// (新たに孤立としてマークしたコミット数, 削除したコミット数) を返す。
+    fn detect_orphaned_commits(
         &self,
         git_repo: &GitRepository,
         database: &mut Database,
+    ) -> Result<(usize, usize)> {
+        let mut tips = git_repo.analyzed_tips(&self.config.commit_selection())?;
+        for refs in database.get_analyzed_refs()? {
+            let selection = CommitSelection {
+                range: Some(refs),
+                ..CommitSelection::default()
+            };
+            if let Ok(resolved) = git_repo.analyzed_tips(&selection) {
+                tips.extend(resolved);
+            }
+        }
+        tips.sort();
+        tips.dedup();
+
+        let reachable: HashSet<String> = git_repo
+            .reachable_commits(&tips)?
+            .into_iter()
+            .map(|oid| oid.to_string())
+            .collect();
+
+        let unreachable: Vec<String> = database
+            .get_commit_hashes()?
+            .into_iter()
+            .filter(|hash| !reachable.contains(hash))
+            .collect();
+
+        let newly_orphaned = database.update_orphaned_commits(&unreachable)?;
+
+        let pruned = if self.config.prune_orphans && !unreachable.is_empty() {
+            database.prune_orphaned_commits()?
+        } else {
+            0
+        };
+
+        Ok((newly_orphaned, pruned))
+    }
+
+    /// コミットバッチからコミット情報・ファイル変更・失敗の記録を抽出
+    fn extract_commit_batch(
+        &self,
+        git_repo: &GitRepository,
         commit_oids: &[git2::Oid],
-    ) -> Result<()> {
-        // コミット情報を抽出
-        let mut commit_infos = Vec::new();
-        let mut all_file_changes = Vec::new();
+        topo_ranks: &HashMap<git2::Oid, i64>,
+    ) -> Result<CommitBatch> {
+        let mut batch = CommitBatch::default();
 
         for oid in commit_oids {
-            // コミット情報を抽出
-            let commit_info = git_repo.extract_commit_info(*oid)?;
-
-            if self.config.verbose {
-                println!(
-                    "    [{}] {}",
-                    &commit_info.commit_hash[..8],
-                    commit_info.message.lines().next().unwrap_or("")
-                );
+            match self.extract_commit(git_repo, *oid, topo_ranks) {
+                Ok((commit_info, file_changes)) => {
+                    if git_repo.is_grafted(*oid) {
+                        batch.grafted_commits += 1;
+                    }
+                    self.progress.commit_analyzed(&commit_info);
+                    batch.commits.push(commit_info);
+                    batch.file_changes.extend(file_changes);
+                }
+                // --keep-going: コミット単位の失敗は記録して次のコミットへ
+                Err(e) if self.config.keep_going && e.is_per_commit() => {
+                    self.progress.commit_failed(&e);
+                    batch.errors.push(AnalysisErrorRecord {
+                        commit_hash: oid.to_string(),
+                        file_path: e.path().map(str::to_string),
+                        category: e.category().to_string(),
+                        message: e.to_string(),
+                    });
+                }
+                Err(e) => return Err(e),
             }
+        }
 
-            commit_infos.push(commit_info.clone());
+        Ok(batch)
+    }
 
-            // ファイル変更情報を抽出
-            let mut file_changes = diff::extract_file_changes(
-                git_repo.inner(),
-                *oid,
-                &commit_info.commit_hash,
-            )?;
+    /// 1コミットのコミット情報とファイル変更を抽出（エラーにはコミットを付与）
+    fn extract_commit(
+        &self,
+        git_repo: &GitRepository,
+        oid: git2::Oid,
+        topo_ranks: &HashMap<git2::Oid, i64>,
+    ) -> Result<(CommitInfo, Vec<FileChange>)> {
+        let commit_hash = oid.to_string();
+        let in_commit = |e: GitHistoryError| e.in_commit(commit_hash.as_str(), None);
 
-            // コミット回数を更新
-            diff::update_commit_counts(&mut file_changes, |file_path| {
-                database.get_commit_count(file_path)
-            })?;
+        // コミット情報を抽出
+        let mut commit_info = git_repo.extract_commit_info(oid).map_err(in_commit)?;
+        commit_info.topo_order = topo_ranks.get(&oid).copied();
+        self.config.author_aliases.apply(&mut commit_info);
 
-            all_file_changes.extend(file_changes);
+        // shallow clone の境界のコミットは親がなく、ツリー全体の追加に見えるので変更を記録しない
+        // （取得する履歴を深くして再解析すると、親との差分が記録される）
+        if git_repo.is_grafted(oid) {
+            return Ok((commit_info, Vec::new()));
         }
 
-        // データベースに挿入
-        database.insert_commits(&commit_infos)?;
-        database.insert_file_changes(&all_file_changes)?;
+        // ファイル変更情報を抽出
+        // （累積コミット回数は全バッチの挿入後にトポロジカル順で再計算する）
+        let file_changes =
+            diff::extract_file_changes(git_repo.inner(), oid, &commit_hash, &self.config.diff)
+                .map_err(in_commit)?
+                .into_iter()
+                .filter(|change| !self.config.path_filter.is_excluded(&change.file_path))
+                .collect();
+
+        Ok((commit_info, file_changes))
+    }
+}
+
+/// 記録済みの行と異なるリネーム検出の設定で解析しようとしていないか確認
+///
+/// 既存のコミットは上書きしないので、設定が混ざると同じリネームが RENAME の1行と
+/// DELETE/ADD の2行の両方の形で残る。記録のない古い実行はリネームを検出していない。
+fn check_rename_detection(database: &Database, detect_renames: bool) -> Result<()> {
+    if database.get_max_topo_order()? < 0 {
+        return Ok(());
+    }
+    let Some(run) = database.get_analysis_runs()?.into_iter().next() else {
+        return Ok(());
+    };
+
+    let recorded = serde_json::from_str::<serde_json::Value>(&run.options)
+        .ok()
+        .and_then(|options| options["detect_renames"].as_bool())
+        .unwrap_or(false);
+    if recorded != detect_renames {
+        return Err(GitHistoryError::ConfigError(format!(
+            "This database was analyzed {} --detect-renames; analyze into a new database to change it",
+            if recorded { "with" } else { "without" }
+        )));
+    }
+    Ok(())
+}
+
+/// 実行履歴に記録する解析オプション（JSON）
+fn run_options(config: &Config) -> String {
+    serde_json::json!({
+        "incremental": config.incremental,
+        "prune_orphans": config.prune_orphans,
+        "resume": config.resume,
+        "keep_going": config.keep_going,
+        "limit": config.limit,
+        "since": config.since,
+        "until": config.until,
+        "first_parent": config.first_parent,
+        "order": config.order.to_string(),
+        "exclude_paths": config.path_filter.patterns(),
+        "diff_algorithm": config.diff.algorithm.to_string(),
+        "diff_context_lines": config.diff.context_lines,
+        "ignore_whitespace": config.diff.ignore_whitespace.map(|mode| mode.to_string()),
+        "detect_renames": config.diff.detect_renames,
+        "bulk_files": config.commit_kinds.bulk_files,
+        "whitespace_share": config.commit_kinds.whitespace_share,
+        "mirror_depth": config.mirror.depth,
+        "rollup_depth": config.rollups.depth,
+        "rollup_bucket": config.rollups.bucket.to_string(),
+        "timezone": config.timezone.to_string(),
+    })
+    .to_string()
+}
+
+/// チェックポイントが今回のコミット一覧の途中を指しているか
+///
+/// 参照が更新されてコミット一覧が変わった場合は再開せずに最初から処理する。
+fn checkpoint_matches(checkpoint: &AnalysisCheckpoint, commit_oids: &[git2::Oid]) -> bool {
+    let processed = checkpoint.processed_commits as usize;
+    checkpoint.total_commits as usize == commit_oids.len()
+        && processed > 0
+        && commit_oids
+            .get(processed - 1)
+            .is_some_and(|oid| oid.to_string() == checkpoint.last_commit_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature, Time};
+    use std::path::Path;
+
+    /// `parents` を親としてトップレベルの `path` を書き換えたコミットを作成し、`reference` を進める
+    fn commit(
+        repo: &Repository,
+        reference: &str,
+        parents: &[git2::Oid],
+        path: &str,
+        content: &str,
+        time: i64,
+    ) -> git2::Oid {
+        let signature = Signature::new("Ana", "ana@example.com", &Time::new(time, 0)).unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let mut builder = repo
+            .treebuilder(parents.first().map(|p| p.tree().unwrap()).as_ref())
+            .unwrap();
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        builder.insert(path, blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = repo
+            .commit(None, &signature, &signature, content, &tree, &parent_refs)
+            .unwrap();
+        repo.reference(reference, oid, true, "test").unwrap();
+        oid
+    }
+
+    fn analyze(repo: &Path, db: &Path, branch: Option<&str>) -> AnalysisResult {
+        let config = Config::new(repo.to_path_buf(), db.to_path_buf())
+            .with_branch(branch.map(str::to_string))
+            .with_incremental(true)
+            .with_prune_orphans(true);
+        Analyzer::new(config).analyze().unwrap()
+    }
+
+    #[test]
+    fn analyzing_another_branch_keeps_earlier_refs_reachable() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("repo");
+        let db = dir.path().join("history.db");
+        let repo = Repository::init(&repo_path).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        let root = commit(&repo, "refs/heads/main", &[], "a.txt", "a\n", 100);
+        commit(&repo, "refs/heads/main", &[root], "a.txt", "a\nb\n", 200);
+        commit(&repo, "refs/heads/feature", &[root], "f.txt", "f\n", 300);
+
+        let first = analyze(&repo_path, &db, None);
+        assert_eq!(first.total_commits, 2);
+
+        // main だけのコミットは feature から辿れないが、以前の実行で解析した HEAD から辿れる
+        let second = analyze(&repo_path, &db, Some("feature"));
+        assert_eq!(second.orphaned_commits, 0);
+        assert_eq!(second.pruned_commits, 0);
+        assert_eq!(second.total_commits, 3);
+
+        // main を書き換えると、どの参照からも辿れないコミットだけが孤立する
+        repo.reference("refs/heads/main", root, true, "rewrite")
+            .unwrap();
+        let third = analyze(&repo_path, &db, Some("feature"));
+        assert_eq!(third.orphaned_commits, 1);
+        assert_eq!(third.pruned_commits, 1);
+        assert_eq!(third.total_commits, 2);
+    }
+
+    #[test]
+    fn shallow_boundary_commits_record_no_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("repo");
+        let db = dir.path().join("history.db");
+        let repo = Repository::init(&repo_path).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        let root = commit(&repo, "refs/heads/main", &[], "a.txt", "a\n", 100);
+        let boundary = commit(&repo, "refs/heads/main", &[root], "b.txt", "b\n", 200);
+        commit(&repo, "refs/heads/main", &[boundary], "c.txt", "c\n", 300);
+        // `--depth 2` で取得した状態を再現（boundary の親は取得していない）
+        std::fs::write(repo.path().join("shallow"), format!("{boundary}\n")).unwrap();
+
+        let result = analyze(&repo_path, &db, None);
+        assert_eq!(result.total_commits, 2);
+        assert_eq!(result.grafted_commits, 1);
+        // boundary をツリー全体（a.txt, b.txt）の追加として記録しない
+        assert_eq!(result.total_files, 1);
+    }
+
+    #[test]
+    fn changing_rename_detection_requires_a_new_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path().join("repo");
+        let repo = Repository::init(&repo_path).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        commit(&repo, "refs/heads/main", &[], "a.txt", "a\n", 100);
+
+        let db = dir.path().join("history.db");
+        analyze(&repo_path, &db, None);
+
+        let detecting = |db: &Path| {
+            let diff = crate::git::DiffStatOptions {
+                detect_renames: true,
+                ..Default::default()
+            };
+            Config::new(repo_path.clone(), db.to_path_buf()).with_diff_options(diff)
+        };
+        let error = Analyzer::new(detecting(&db)).analyze().unwrap_err();
+        assert!(matches!(error, GitHistoryError::ConfigError(_)));
+
+        let fresh = Analyzer::new(detecting(&dir.path().join("renames.db")))
+            .analyze()
+            .unwrap();
+        assert_eq!(fresh.total_commits, 1);
+    }
+
+    #[test]
+    fn checkpoint_matches_only_the_same_commit_list() {
+        let oids: Vec<git2::Oid> = (1..=3u8)
+            .map(|i| git2::Oid::from_bytes(&[i; 20]).unwrap())
+            .collect();
+        let checkpoint = AnalysisCheckpoint {
+            selection_key: "repo|HEAD".to_string(),
+            total_commits: 3,
+            processed_commits: 2,
+            last_commit_hash: oids[1].to_string(),
+            base_topo_order: 0,
+        };
+        assert!(checkpoint_matches(&checkpoint, &oids));
 
-        Ok(())
+        // 参照が更新されてコミット数が変わった
+        assert!(!checkpoint_matches(&checkpoint, &oids[..2]));
+        // 同じ位置のコミットが違う
+        let moved = [oids[0], oids[2], oids[1]];
+        assert!(!checkpoint_matches(&checkpoint, &moved));
+        // 何も保存していない
+        let empty = AnalysisCheckpoint {
+            processed_commits: 0,
+            ..checkpoint
+        };
+        assert!(!checkpoint_matches(&empty, &oids));
     }
 }
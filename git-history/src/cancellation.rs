@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 解析の中断要求を伝えるトークン
+///
+/// クローンしたトークンは状態を共有するため、別スレッド（シグナルハンドラなど）から中断できる。
+/// `Analyzer` はコミットバッチの区切りでのみ確認するので、処理中のバッチは保存されてから止まる。
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// 新しいトークンを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 中断を要求
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// 中断が要求されているか
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,45 @@
+pub mod analyze;
+pub mod coupling;
+pub mod diff;
+pub mod hotspots;
+pub mod output;
+pub mod ownership;
+pub mod progress;
+pub mod releases;
+pub mod serve;
+pub mod snapshots;
+pub mod timeline;
+pub mod tui;
+
+use git_history::config::file::ConfigFile;
+use git_history::git::remote;
+use std::path::PathBuf;
+
+/// レポートが読むデータベースのパス（CLI → 設定ファイル → 既定値）
+pub fn database_path(db: &Option<PathBuf>, settings: &ConfigFile) -> PathBuf {
+    db.clone()
+        .or_else(|| settings.output.database.clone())
+        .unwrap_or_else(|| PathBuf::from("git-history.db"))
+}
+
+/// レポートが読むリポジトリのパス（CLI → 設定ファイル → カレントディレクトリ）
+///
+/// 設定ファイルのリポジトリがクローンURLの場合は `analyze` が作成したミラーを使う。
+pub fn repository_path(repo: &Option<PathBuf>, settings: &ConfigFile) -> PathBuf {
+    if let Some(repo) = repo {
+        return repo.clone();
+    }
+
+    match settings.repository.path.as_deref() {
+        Some(url) if remote::is_remote_url(url) => {
+            let cache_dir = settings
+                .repository
+                .mirror_dir
+                .clone()
+                .unwrap_or_else(remote::default_cache_dir);
+            remote::mirror_path(url, &cache_dir)
+        }
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from("."),
+    }
+}
@@ -0,0 +1,327 @@
+use crate::commands::progress::CliProgress;
+use clap::Args;
+use git_history::analysis::rollups::TimeBucket;
+use git_history::analysis::timeline::Timezone;
+use git_history::config::file::ConfigFile;
+use git_history::config::filters::PathFilter;
+use git_history::git::remote;
+use git_history::git::{CommitOrder, DiffAlgorithm, IgnoreWhitespace};
+use git_history::{
+    AnalysisResult, Analyzer, CancellationToken, Config, ErrorCategory, GitHistoryError, Result,
+    config,
+};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// `analyze` サブコマンドの引数
+///
+/// 指定しなかったオプションは設定ファイル（git-history.toml）の値、それもなければ既定値を使う。
+#[derive(Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Repository path or clone URL (https://, ssh://, git@host:path, file://) [default: .]
+    #[arg(short, long)]
+    repo: Option<String>,
+
+    /// Directory for bare mirrors of remote repositories (default: ~/.cache/git-history/mirrors)
+    #[arg(long)]
+    mirror_dir: Option<PathBuf>,
+
+    /// Fetch only this many commits from each ref tip when mirroring (shallow fetch)
+    #[arg(long)]
+    depth: Option<u32>,
+
+    /// Refs to fetch when mirroring (repeatable; default: all branches and tags)
+    #[arg(long = "fetch-ref", value_name = "REFSPEC")]
+    fetch_refs: Vec<String>,
+
+    /// Output database path [default: git-history.db]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Target branch (default: all branches from HEAD)
+    #[arg(short, long, conflicts_with = "range")]
+    branch: Option<String>,
+
+    /// Revision range to analyze (e.g. v1.0..v2.0, main...feature)
+    #[arg(long)]
+    range: Option<String>,
+
+    /// Only commits on or after this date (YYYY-MM-DD, RFC 3339 or Unix timestamp)
+    #[arg(long, value_parser = parse_since)]
+    since: Option<i64>,
+
+    /// Only commits on or before this date (YYYY-MM-DD, RFC 3339 or Unix timestamp)
+    #[arg(long, value_parser = parse_until)]
+    until: Option<i64>,
+
+    /// Follow only the first parent of merge commits
+    #[arg(long)]
+    first_parent: bool,
+
+    /// Commit order: time (newest first), topo (children before parents), reverse (oldest first) [default: time]
+    #[arg(long)]
+    order: Option<CommitOrder>,
+
+    /// Skip files matching this glob (repeatable, e.g. 'vendor/**', '*.lock'; replaces filters.exclude_paths)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude_paths: Vec<String>,
+
+    /// Incremental mode (append to existing DB)
+    #[arg(short, long)]
+    incremental: bool,
+
+    /// In incremental mode, delete commits that are no longer reachable instead of only marking them
+    #[arg(long)]
+    prune_orphans: bool,
+
+    /// Record commits that fail to analyze in the analysis_errors table and continue
+    #[arg(long)]
+    keep_going: bool,
+
+    /// Ignore the checkpoint of an interrupted run and start over
+    #[arg(long)]
+    restart: bool,
+
+    /// Verbose output
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Limit number of commits to analyze
+    #[arg(short, long)]
+    limit: Option<usize>,
+
+    /// Directory depth for the dir_rollups table [default: 2]
+    #[arg(long)]
+    rollup_depth: Option<usize>,
+
+    /// Time bucket for the dir_rollups table (day, week, month) [default: month]
+    #[arg(long)]
+    rollup_bucket: Option<TimeBucket>,
+
+    /// Timezone for activity_timeline buckets (utc, author, or a fixed offset like +09:00; zone names such as Europe/Berlin are not supported, so DST is not applied) [default: utc]
+    #[arg(long)]
+    timezone: Option<Timezone>,
+
+    /// Diff algorithm used to count added/deleted lines (myers, patience, minimal; histogram is not supported by libgit2) [default: myers]
+    #[arg(long)]
+    diff_algorithm: Option<DiffAlgorithm>,
+
+    /// Context lines around each hunk; affects how nearby hunks merge [default: 3]
+    #[arg(long, value_name = "LINES")]
+    context_lines: Option<u32>,
+
+    /// Also record line counts that ignore whitespace changes (eol, change, all)
+    #[arg(long, value_name = "MODE")]
+    ignore_whitespace: Option<IgnoreWhitespace>,
+
+    /// Record deleted+added pairs as one RENAME row with old_path (the database must not mix both modes)
+    #[arg(long)]
+    detect_renames: bool,
+
+    /// Tag commits touching more files than this as BULK (excluded from reports) [default: 100]
+    #[arg(long, value_name = "FILES")]
+    bulk_files: Option<usize>,
+}
+
+/// `--since` の値を解析
+fn parse_since(value: &str) -> std::result::Result<i64, String> {
+    config::parse_date(value, false).map_err(|e| e.to_string())
+}
+
+/// `--until` の値を解析（日付のみの場合はその日の終わり）
+fn parse_until(value: &str) -> std::result::Result<i64, String> {
+    config::parse_date(value, true).map_err(|e| e.to_string())
+}
+
+/// 解析を実行して概要を出力
+pub fn run(args: &AnalyzeArgs, settings: &ConfigFile) -> Result<()> {
+    let config = build_config(args, settings)?;
+    // FORMATTING のしきい値を設定していても、空白のみの変更行数は --ignore-whitespace のときだけ記録される
+    let formatting_undetectable = settings.thresholds.formatting_whitespace_share.is_some()
+        && config.diff.ignore_whitespace.is_none();
+
+    // アナライザーを作成して実行（進捗は標準エラー出力に表示）
+    let output_db = config.output_db.clone();
+    let analyzer = Analyzer::new(config)
+        .with_progress(CliProgress::new(args.verbose))
+        .with_cancellation(install_interrupt_handler());
+
+    let analysis_result = analyzer.analyze()?;
+    print_analysis_summary(&analysis_result, &output_db);
+    if formatting_undetectable {
+        eprintln!(
+            "⚠️  thresholds.formatting_whitespace_share has no effect without --ignore-whitespace"
+        );
+    }
+    println!("\n✨ Success!");
+    if analysis_result.total_commits == 0 {
+        println!("⚠️  No commits found in the repository.");
+    }
+    Ok(())
+}
+
+/// 設定ファイルの値にCLIで指定した値を上書きして設定を作成
+fn build_config(args: &AnalyzeArgs, settings: &ConfigFile) -> Result<Config> {
+    let mut config = settings.to_config()?;
+
+    if let Some(repo) = &args.repo {
+        config.repo_path = PathBuf::from(repo);
+        config = config.with_remote(remote::is_remote_url(repo).then(|| repo.clone()));
+    }
+    if let Some(output) = &args.output {
+        config.output_db = output.clone();
+    }
+
+    let mut mirror = config.mirror.clone();
+    if let Some(mirror_dir) = &args.mirror_dir {
+        mirror.cache_dir = mirror_dir.clone();
+    }
+    mirror.depth = args.depth.or(mirror.depth);
+    if !args.fetch_refs.is_empty() {
+        mirror.refspecs = args.fetch_refs.clone();
+    }
+    config = config.with_mirror(mirror);
+
+    // branch と range は排他なので、CLIでどちらかを指定したら設定ファイルの両方を置き換える
+    if args.branch.is_some() || args.range.is_some() {
+        config = config
+            .with_branch(args.branch.clone())
+            .with_revision_range(args.range.clone());
+    }
+    let (since, until) = (args.since.or(config.since), args.until.or(config.until));
+    config = config.with_date_range(since, until);
+    if let Some(order) = args.order {
+        config = config.with_order(order);
+    }
+    if args.first_parent {
+        config = config.with_first_parent(true);
+    }
+    let limit = args.limit.or(config.limit);
+    config = config.with_limit(limit);
+    if !args.exclude_paths.is_empty() {
+        config = config.with_path_filter(PathFilter::new(&args.exclude_paths)?);
+    }
+
+    if args.incremental {
+        config = config.with_incremental(true);
+    }
+    if args.prune_orphans {
+        config = config.with_prune_orphans(true);
+    }
+    if args.keep_going {
+        config = config.with_keep_going(true);
+    }
+    if config.prune_orphans && !config.incremental {
+        return Err(GitHistoryError::ConfigError(
+            "--prune-orphans requires --incremental".to_string(),
+        ));
+    }
+
+    let mut rollups = config.rollups;
+    rollups.depth = args.rollup_depth.unwrap_or(rollups.depth);
+    rollups.bucket = args.rollup_bucket.unwrap_or(rollups.bucket);
+    if rollups.depth == 0 {
+        return Err(GitHistoryError::ConfigError(
+            "--rollup-depth must be at least 1".to_string(),
+        ));
+    }
+    let timezone = args.timezone.unwrap_or(config.timezone);
+
+    let mut diff = config.diff;
+    diff.algorithm = args.diff_algorithm.unwrap_or(diff.algorithm);
+    diff.context_lines = args.context_lines.unwrap_or(diff.context_lines);
+    diff.ignore_whitespace = args.ignore_whitespace.or(diff.ignore_whitespace);
+    diff.detect_renames |= args.detect_renames;
+
+    let mut commit_kinds = config.commit_kinds;
+    commit_kinds.bulk_files = args.bulk_files.unwrap_or(commit_kinds.bulk_files);
+    if commit_kinds.bulk_files == 0 {
+        return Err(GitHistoryError::ConfigError(
+            "--bulk-files must be at least 1".to_string(),
+        ));
+    }
+
+    Ok(config
+        .with_rollups(rollups)
+        .with_timezone(timezone)
+        .with_diff_options(diff)
+        .with_commit_kinds(commit_kinds)
+        .with_resume(!args.restart)
+        .with_verbose(args.verbose))
+}
+
+/// Ctrl-C で解析を中断するトークンを作成
+///
+/// 1回目は処理中のバッチを保存してから止め、2回目は即座に終了する。
+fn install_interrupt_handler() -> CancellationToken {
+    let cancellation = CancellationToken::new();
+    let handler_token = cancellation.clone();
+
+    let installed = ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() {
+            process::exit(ErrorCategory::Cancelled.exit_code());
+        }
+        handler_token.cancel();
+        eprintln!("\n⏸  Interrupted: finishing the current batch (press Ctrl-C again to abort)");
+    });
+    if let Err(e) = installed {
+        eprintln!("⚠️  Could not install Ctrl-C handler: {}", e);
+    }
+
+    cancellation
+}
+
+/// 解析結果の概要を表示
+fn print_analysis_summary(result: &AnalysisResult, output_db: &Path) {
+    println!("\n📊 Analysis complete!");
+    println!(
+        "  Repository: {} ({})",
+        result.repository_path.display(),
+        result.repository_kind
+    );
+    println!("  Commits processed: {}", result.processed_commits);
+    if result.resumed_commits > 0 {
+        println!(
+            "  Resumed after: {} commits stored by the interrupted run",
+            result.resumed_commits
+        );
+    }
+    if result.failed_commits > 0 {
+        println!(
+            "  ⚠️  {} commits failed and were recorded in analysis_errors",
+            result.failed_commits
+        );
+    }
+    if result.grafted_commits > 0 {
+        println!(
+            "  ⚠️  {} shallow boundary commits have no recorded changes (fetch more history with a larger --depth)",
+            result.grafted_commits
+        );
+    }
+    println!("  Total commits: {}", result.total_commits);
+    println!("  Total files: {}", result.total_files);
+    if result.orphaned_commits > 0 {
+        println!(
+            "  ⚠️  {} commits are no longer reachable and were marked as orphaned",
+            result.orphaned_commits
+        );
+    }
+    if result.pruned_commits > 0 {
+        println!("  Pruned orphaned commits: {}", result.pruned_commits);
+    }
+    if result.flagged_commits > 0 {
+        println!(
+            "  Bulk/formatting/rename-only commits: {} (excluded from reports, see commit_kinds)",
+            result.flagged_commits
+        );
+    }
+    println!(
+        "  Processing time: {:.2}s",
+        result.processing_time.as_secs_f64()
+    );
+    println!(
+        "  Database: {} (run #{})",
+        output_db.display(),
+        result.run_id
+    );
+}
@@ -0,0 +1,98 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::coupling::{self, CouplingOptions};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use std::path::PathBuf;
+
+/// `coupling` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct CouplingArgs {
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Skip commits that touch more files than this [default: 50]
+    #[arg(long)]
+    max_files: Option<usize>,
+
+    /// Minimum number of commits in which both files changed [default: 3]
+    #[arg(long)]
+    min_co_changes: Option<usize>,
+
+    /// Minimum confidence (either direction) to report [default: 0.0]
+    #[arg(long)]
+    min_confidence: Option<f64>,
+
+    /// Only report pairs in different directories
+    #[arg(long)]
+    cross_directory: bool,
+
+    /// Number of rows to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    top: usize,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// 同時変更分析を実行して出力
+pub fn run(args: &CouplingArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let thresholds = &settings.thresholds;
+    let mut database = Database::open_existing(&commands::database_path(&args.db, settings))?
+        .with_flagged_commits(args.include_flagged);
+    let options = CouplingOptions {
+        max_files_per_commit: args
+            .max_files
+            .or(thresholds.coupling_max_files)
+            .unwrap_or(50),
+        min_co_changes: args
+            .min_co_changes
+            .or(thresholds.coupling_min_co_changes)
+            .unwrap_or(3),
+        min_confidence: args
+            .min_confidence
+            .or(thresholds.coupling_min_confidence)
+            .unwrap_or(0.0),
+        cross_directory_only: args.cross_directory,
+    };
+
+    let mut couplings = coupling::analyze_coupling(&mut database, &options)?;
+    couplings.truncate(args.top);
+
+    let rows: Vec<Vec<String>> = couplings
+        .iter()
+        .map(|c| {
+            vec![
+                c.file_a.clone(),
+                c.file_b.clone(),
+                c.co_changes.to_string(),
+                output::percent(c.support),
+                output::percent(c.confidence_ab),
+                output::percent(c.confidence_ba),
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &[
+            "FILE A",
+            "FILE B",
+            "CO-CHANGES",
+            "SUPPORT",
+            "CONF A→B",
+            "CONF B→A",
+        ],
+        &rows,
+        &couplings,
+    )
+}
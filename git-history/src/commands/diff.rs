@@ -0,0 +1,345 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::analysis::comparison::{
+    self, AuthorChange, ComparisonOptions, ComparisonReport, ComparisonSide, HotspotChange, Period,
+};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use git_history::{GitHistoryError, Result};
+use std::path::{Path, PathBuf};
+
+/// `diff` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Database to report on (the "target" side) [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Database to compare against (the "base" side) [default: same as --db]
+    #[arg(long)]
+    base_db: Option<PathBuf>,
+
+    /// Base period: START..END (dates or timestamps, either side optional) or a quarter like 2024-Q1
+    #[arg(long, value_name = "PERIOD", value_parser = parse_period)]
+    base: Option<Period>,
+
+    /// Target period, in the same format as --base
+    #[arg(long, value_name = "PERIOD", value_parser = parse_period)]
+    target: Option<Period>,
+
+    /// Directory depth used for per-directory churn
+    #[arg(long, default_value_t = 2)]
+    depth: usize,
+
+    /// Number of top files treated as hotspots, and of directories to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    top: usize,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// `--base` / `--target` の値を解析
+fn parse_period(value: &str) -> std::result::Result<Period, String> {
+    Period::parse(value).map_err(|e| e.to_string())
+}
+
+/// 2つの期間または2つのデータベースを比較して出力
+pub fn run(args: &DiffArgs, settings: &ConfigFile) -> Result<()> {
+    if args.base_db.is_none() && args.base.is_none() && args.target.is_none() {
+        return Err(GitHistoryError::ConfigError(
+            "diff needs --base-db or at least one of --base/--target".to_string(),
+        ));
+    }
+
+    let format = output::resolve_format(args.format, settings);
+    let target_path = commands::database_path(&args.db, settings);
+    let base_path = args.base_db.clone().unwrap_or_else(|| target_path.clone());
+
+    let target_db =
+        Database::open_existing(&target_path)?.with_flagged_commits(args.include_flagged);
+    let base_db = if base_path == target_path {
+        None
+    } else {
+        Some(Database::open_existing(&base_path)?.with_flagged_commits(args.include_flagged))
+    };
+
+    let two_databases = base_db.is_some();
+    let base = ComparisonSide {
+        label: side_label(&base_path, args.base.as_ref(), two_databases),
+        database: base_db.as_ref().unwrap_or(&target_db),
+        period: args.base.unwrap_or_default(),
+    };
+    let target = ComparisonSide {
+        label: side_label(&target_path, args.target.as_ref(), two_databases),
+        database: &target_db,
+        period: args.target.unwrap_or_default(),
+    };
+    let options = ComparisonOptions {
+        depth: args.depth,
+        top: args.top,
+    };
+
+    let mut report = comparison::compare(&base, &target, &options)?;
+    report.directories.truncate(args.top);
+
+    match format {
+        OutputFormat::Table => print_comparison(&report, args.top),
+        OutputFormat::Csv => output::print_csv(
+            &["SECTION", "KEY", "BASE", "TARGET", "DELTA"],
+            &csv_rows(&report),
+        ),
+        OutputFormat::Json => output::print_json(&report)?,
+    }
+    Ok(())
+}
+
+/// 比較する側の表示名（2つのDBを比較する場合はファイル名、期間があれば期間を付ける）
+fn side_label(path: &Path, period: Option<&Period>, two_databases: bool) -> String {
+    let period = period.map(describe_period);
+    match (two_databases, period) {
+        (true, Some(period)) => format!("{} ({})", path.display(), period),
+        (true, None) => path.display().to_string(),
+        (false, Some(period)) => period,
+        (false, None) => "all history".to_string(),
+    }
+}
+
+/// 期間を `YYYY-MM-DD..YYYY-MM-DD` 形式で表示（`until` は期間に含まれる最後の時刻なので、その日付が最終日）
+fn describe_period(period: &Period) -> String {
+    let date = |timestamp: Option<i64>| {
+        timestamp
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+    format!("{}..{}", date(period.since), date(period.until))
+}
+
+/// 符号付きの差分
+fn delta(base: i64, target: i64) -> String {
+    format!("{:+}", target - base)
+}
+
+/// 概要の行（指標, 比較元, 比較先）
+fn summary_metrics(report: &ComparisonReport) -> Vec<(&'static str, i64, i64)> {
+    let (base, target) = (&report.base, &report.target);
+    vec![
+        ("commits", base.commits as i64, target.commits as i64),
+        ("authors", base.authors as i64, target.authors as i64),
+        (
+            "files changed",
+            base.files_changed as i64,
+            target.files_changed as i64,
+        ),
+        ("lines added", base.lines_added, target.lines_added),
+        ("lines deleted", base.lines_deleted, target.lines_deleted),
+        ("churn", base.churn(), target.churn()),
+    ]
+}
+
+/// 順位の表示（上位外は `-`）
+fn rank(rank: Option<usize>) -> String {
+    rank.map(|r| format!("#{}", r))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// テーブル形式で出力
+fn print_comparison(report: &ComparisonReport, top: usize) {
+    println!(
+        "📊 Comparing {} → {}\n",
+        report.base.label, report.target.label
+    );
+    let rows: Vec<Vec<String>> = summary_metrics(report)
+        .into_iter()
+        .map(|(metric, base, target)| {
+            vec![
+                metric.to_string(),
+                base.to_string(),
+                target.to_string(),
+                delta(base, target),
+            ]
+        })
+        .collect();
+    output::print_table(&["METRIC", "BASE", "TARGET", "DELTA"], &rows);
+
+    let hotspot_rows = |hotspots: &[HotspotChange]| -> Vec<Vec<String>> {
+        hotspots
+            .iter()
+            .map(|h| {
+                vec![
+                    h.file_path.clone(),
+                    rank(h.base_rank),
+                    rank(h.target_rank),
+                    h.base_changes.to_string(),
+                    h.target_changes.to_string(),
+                ]
+            })
+            .collect()
+    };
+    let hotspot_headers = [
+        "FILE",
+        "BASE RANK",
+        "TARGET RANK",
+        "BASE CHANGES",
+        "TARGET CHANGES",
+    ];
+    if !report.new_hotspots.is_empty() {
+        println!("\n🔥 New hotspots (entered the top {}):", top);
+        output::print_table(&hotspot_headers, &hotspot_rows(&report.new_hotspots));
+    }
+    if !report.cooled_hotspots.is_empty() {
+        println!("\n🧊 Cooled hotspots (left the top {}):", top);
+        output::print_table(&hotspot_headers, &hotspot_rows(&report.cooled_hotspots));
+    }
+
+    let author_rows = |authors: &[AuthorChange]| -> Vec<Vec<String>> {
+        authors
+            .iter()
+            .map(|a| {
+                vec![
+                    a.author_name.clone(),
+                    a.author_email.clone(),
+                    a.commits.to_string(),
+                ]
+            })
+            .collect()
+    };
+    if !report.joined_authors.is_empty() {
+        println!("\n👋 Authors who joined:");
+        output::print_table(
+            &["NAME", "EMAIL", "COMMITS"],
+            &author_rows(&report.joined_authors),
+        );
+    }
+    if !report.left_authors.is_empty() {
+        println!("\n🚪 Authors who left:");
+        output::print_table(
+            &["NAME", "EMAIL", "COMMITS"],
+            &author_rows(&report.left_authors),
+        );
+    }
+
+    if !report.directories.is_empty() {
+        println!("\n📁 Directories with the largest churn change:");
+        let rows: Vec<Vec<String>> = report
+            .directories
+            .iter()
+            .map(|d| {
+                vec![
+                    d.path.clone(),
+                    d.base_churn.to_string(),
+                    d.target_churn.to_string(),
+                    delta(d.base_churn, d.target_churn),
+                    format!("{:+}", d.base_net_lines),
+                    format!("{:+}", d.target_net_lines),
+                ]
+            })
+            .collect();
+        output::print_table(
+            &[
+                "DIRECTORY",
+                "BASE CHURN",
+                "TARGET CHURN",
+                "DELTA",
+                "BASE NET LINES",
+                "TARGET NET LINES",
+            ],
+            &rows,
+        );
+    }
+}
+
+/// CSV用の縦持ちの行（セクション, キー, 比較元, 比較先, 差分）
+fn csv_rows(report: &ComparisonReport) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = summary_metrics(report)
+        .into_iter()
+        .map(|(metric, base, target)| {
+            vec![
+                "summary".to_string(),
+                metric.to_string(),
+                base.to_string(),
+                target.to_string(),
+                delta(base, target),
+            ]
+        })
+        .collect();
+
+    for (section, hotspots) in [
+        ("new_hotspot", &report.new_hotspots),
+        ("cooled_hotspot", &report.cooled_hotspots),
+    ] {
+        rows.extend(hotspots.iter().map(|h| {
+            vec![
+                section.to_string(),
+                h.file_path.clone(),
+                h.base_changes.to_string(),
+                h.target_changes.to_string(),
+                delta(h.base_changes, h.target_changes),
+            ]
+        }));
+    }
+
+    rows.extend(report.joined_authors.iter().map(|a| {
+        let commits = a.commits as i64;
+        vec![
+            "joined_author".to_string(),
+            a.author_email.clone(),
+            "0".to_string(),
+            commits.to_string(),
+            delta(0, commits),
+        ]
+    }));
+    rows.extend(report.left_authors.iter().map(|a| {
+        let commits = a.commits as i64;
+        vec![
+            "left_author".to_string(),
+            a.author_email.clone(),
+            commits.to_string(),
+            "0".to_string(),
+            delta(commits, 0),
+        ]
+    }));
+
+    rows.extend(report.directories.iter().flat_map(|d| {
+        [
+            vec![
+                "directory_churn".to_string(),
+                d.path.clone(),
+                d.base_churn.to_string(),
+                d.target_churn.to_string(),
+                delta(d.base_churn, d.target_churn),
+            ],
+            vec![
+                "directory_net_lines".to_string(),
+                d.path.clone(),
+                d.base_net_lines.to_string(),
+                d.target_net_lines.to_string(),
+                delta(d.base_net_lines, d.target_net_lines),
+            ],
+        ]
+    }));
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn period_labels_show_the_last_included_day() {
+        let label = |value: &str| describe_period(&Period::parse(value).unwrap());
+
+        assert_eq!(label("2024-Q1"), "2024-01-01..2024-03-31");
+        assert_eq!(label("2024-Q4"), "2024-10-01..2024-12-31");
+        assert_eq!(label("2024-01-01..2024-01-31"), "2024-01-01..2024-01-31");
+        assert_eq!(label("2024-01-01.."), "2024-01-01..");
+    }
+}
@@ -0,0 +1,89 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::hotspots::{self, HotspotOptions};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use git_history::git::GitRepository;
+use std::path::PathBuf;
+
+/// `hotspots` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct HotspotsArgs {
+    /// Repository path used to read file contents [default: .]
+    #[arg(short, long)]
+    repo: Option<PathBuf>,
+
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Revision whose file contents are used for the complexity proxy
+    #[arg(long, default_value = "HEAD")]
+    rev: String,
+
+    /// Also list non-code files (config, docs); they always score 0
+    #[arg(long)]
+    include_non_code: bool,
+
+    /// Number of rows to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    top: usize,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// ホットスポット分析を実行して出力
+pub fn run(args: &HotspotsArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let git_repo = GitRepository::open(&commands::repository_path(&args.repo, settings))?;
+    let mut database = Database::open_existing(&commands::database_path(&args.db, settings))?
+        .with_flagged_commits(args.include_flagged);
+    let options = HotspotOptions {
+        revision: args.rev.clone(),
+        include_non_code: args.include_non_code,
+    };
+
+    let mut hotspots = hotspots::analyze_hotspots(&git_repo, &mut database, &options)?;
+    hotspots.truncate(args.top);
+
+    let rows: Vec<Vec<String>> = hotspots
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            vec![
+                (i + 1).to_string(),
+                h.file_path.clone(),
+                h.language.clone(),
+                h.change_count.to_string(),
+                h.code_lines.to_string(),
+                format!("{:.2}", h.mean_depth),
+                h.functions.to_string(),
+                format!("{:.3}", h.score),
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &[
+            "RANK",
+            "FILE",
+            "LANG",
+            "CHANGES",
+            "CODE LINES",
+            "MEAN DEPTH",
+            "FUNCS",
+            "SCORE",
+        ],
+        &rows,
+        &hotspots,
+    )
+}
@@ -0,0 +1,110 @@
+use clap::ValueEnum;
+use git_history::Result;
+use git_history::config::file::ConfigFile;
+use serde::Serialize;
+
+/// レポートの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// 出力形式を決定（CLI → 設定ファイル → table）
+pub fn resolve_format(format: Option<OutputFormat>, settings: &ConfigFile) -> OutputFormat {
+    format
+        .or_else(|| {
+            let name = settings.output.format.as_deref()?;
+            OutputFormat::from_str(name, true).ok()
+        })
+        .unwrap_or(OutputFormat::Table)
+}
+
+/// 行データを指定形式で出力（JSONはdataをそのままシリアライズ）
+pub fn print_report<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    data: &T,
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => print_table(headers, rows),
+        OutputFormat::Csv => print_csv(headers, rows),
+        OutputFormat::Json => print_json(data)?,
+    }
+    Ok(())
+}
+
+/// 桁揃えしたテーブルを出力
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| format!("{:<width$}", cell, width = widths[i]))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("  ")
+    );
+    for row in rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+/// CSVを出力
+pub fn print_csv(headers: &[&str], rows: &[Vec<String>]) {
+    let escape = |cell: &str| {
+        if cell.contains([',', '"', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        }
+    };
+
+    println!(
+        "{}",
+        headers
+            .iter()
+            .map(|h| escape(h))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    for row in rows {
+        println!(
+            "{}",
+            row.iter().map(|c| escape(c)).collect::<Vec<_>>().join(",")
+        );
+    }
+}
+
+/// JSONを出力
+pub fn print_json<T: Serialize + ?Sized>(data: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| git_history::GitHistoryError::AnalysisError(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// 割合をパーセント表記に変換
+pub fn percent(value: f64) -> String {
+    format!("{:.1}%", value * 100.0)
+}
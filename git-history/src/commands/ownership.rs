@@ -0,0 +1,139 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::ownership::{self, OwnershipOptions, PathOwnership};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use std::path::PathBuf;
+
+/// `ownership` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct OwnershipArgs {
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Directory depth used for per-directory ownership
+    #[arg(long, default_value_t = 2)]
+    depth: usize,
+
+    /// Half-life in days for weighting churn by recency [default: 180]
+    #[arg(long)]
+    half_life_days: Option<f64>,
+
+    /// Authors with no commits for this many days are treated as inactive [default: 90]
+    #[arg(long)]
+    inactive_days: Option<i64>,
+
+    /// Report per-file ownership instead of per-directory
+    #[arg(long)]
+    files: bool,
+
+    /// Number of rows to show (sorted by bus factor, then knowledge-loss risk)
+    #[arg(short = 'n', long, default_value_t = 20)]
+    top: usize,
+
+    /// Write a CODEOWNERS draft to this path
+    #[arg(long)]
+    codeowners: Option<PathBuf>,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// 所有者分析を実行して出力
+pub fn run(args: &OwnershipArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let thresholds = &settings.thresholds;
+    let database = Database::open_existing(&commands::database_path(&args.db, settings))?
+        .with_flagged_commits(args.include_flagged);
+    let options = OwnershipOptions {
+        half_life_days: args
+            .half_life_days
+            .or(thresholds.ownership_half_life_days)
+            .unwrap_or(180.0),
+        inactive_days: args
+            .inactive_days
+            .or(thresholds.ownership_inactive_days)
+            .unwrap_or(90),
+        depth: args.depth,
+    };
+
+    let report = ownership::analyze_ownership(&database, &options)?;
+
+    let mut entries: Vec<&PathOwnership> = if args.files {
+        report.files.iter().collect()
+    } else {
+        report.directories.iter().collect()
+    };
+    // バスファクターが小さく、知識喪失リスクが高いものを優先
+    entries.sort_by(|a, b| {
+        a.bus_factor
+            .cmp(&b.bus_factor)
+            .then_with(|| b.knowledge_loss_risk.total_cmp(&a.knowledge_loss_risk))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    entries.truncate(args.top);
+
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            let primary = entry.primary_owner();
+            vec![
+                entry.path.clone(),
+                primary
+                    .map(|o| {
+                        let suffix = if o.inactive { " (inactive)" } else { "" };
+                        format!("{}{}", o.author_email, suffix)
+                    })
+                    .unwrap_or_default(),
+                primary
+                    .map(|o| output::percent(o.share))
+                    .unwrap_or_default(),
+                entry.bus_factor.to_string(),
+                output::percent(entry.knowledge_loss_risk),
+                entry.owners.len().to_string(),
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &[
+            "PATH",
+            "PRIMARY OWNER",
+            "SHARE",
+            "BUS FACTOR",
+            "RISK",
+            "CONTRIBUTORS",
+        ],
+        &rows,
+        &entries,
+    )?;
+
+    if format == OutputFormat::Table {
+        let inactive: Vec<_> = report.authors.iter().filter(|a| a.inactive).collect();
+        if !inactive.is_empty() {
+            println!(
+                "\n⚠️  Inactive authors (no commits for {}+ days):",
+                options.inactive_days
+            );
+            for author in inactive {
+                println!("  {} <{}>", author.author_name, author.author_email);
+            }
+        }
+    }
+
+    if let Some(path) = &args.codeowners {
+        std::fs::write(path, ownership::generate_codeowners(&report))?;
+        eprintln!("✓ CODEOWNERS draft written: {}", path.display());
+    }
+
+    Ok(())
+}
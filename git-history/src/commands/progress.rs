@@ -0,0 +1,76 @@
+use git_history::GitHistoryError;
+use git_history::database::CommitInfo;
+use git_history::progress::{CommitProgress, Phase, ProgressObserver};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+/// 標準エラー出力に進捗バーを表示するオブザーバー
+pub struct CliProgress {
+    bar: ProgressBar,
+    verbose: bool,
+}
+
+impl CliProgress {
+    pub fn new(verbose: bool) -> Self {
+        let bar = ProgressBar::hidden();
+        bar.set_style(
+            ProgressStyle::with_template("  {bar:40.cyan/blue} {pos}/{len} commits  {msg}")
+                .expect("valid progress template")
+                .progress_chars("█▉▊▋▌▍▎▏ "),
+        );
+        Self { bar, verbose }
+    }
+}
+
+impl ProgressObserver for CliProgress {
+    fn phase_started(&self, phase: Phase) {
+        match phase {
+            Phase::ProcessingCommits => self
+                .bar
+                .set_draw_target(indicatif::ProgressDrawTarget::stderr()),
+            Phase::SyncingMirror => eprintln!("🌐 {}...", phase),
+            _ => {}
+        }
+    }
+
+    fn phase_finished(&self, phase: Phase, elapsed: Duration) {
+        if phase == Phase::ProcessingCommits {
+            self.bar.finish_and_clear();
+        }
+        eprintln!("✓ {} ({:.2}s)", phase, elapsed.as_secs_f64());
+    }
+
+    fn commit_analyzed(&self, commit: &CommitInfo) {
+        if self.verbose {
+            self.bar.println(format!(
+                "    [{}] {}",
+                &commit.commit_hash[..8],
+                commit.message.lines().next().unwrap_or("")
+            ));
+        }
+    }
+
+    fn commit_failed(&self, error: &GitHistoryError) {
+        self.bar
+            .println(format!("  ⚠️  [{}] {}", error.category(), error));
+    }
+
+    fn resumed_from_checkpoint(&self, skipped: usize, total: usize) {
+        eprintln!(
+            "↻ Resuming interrupted analysis: {}/{} commits already stored",
+            skipped, total
+        );
+    }
+
+    fn commits_processed(&self, progress: &CommitProgress) {
+        self.bar.set_length(progress.total as u64);
+        self.bar.set_position(progress.processed as u64);
+
+        let eta = progress
+            .eta()
+            .map(|eta| format!("ETA {}s", eta.as_secs()))
+            .unwrap_or_else(|| "ETA --".to_string());
+        self.bar
+            .set_message(format!("{:.1} commits/s, {}", progress.throughput(), eta));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use chrono::DateTime;
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::releases;
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use git_history::git::GitRepository;
+use std::path::PathBuf;
+
+/// `releases` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct ReleasesArgs {
+    /// Repository path [default: .]
+    #[arg(short, long)]
+    repo: Option<PathBuf>,
+
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Only show the most recent N releases
+    #[arg(short = 'n', long)]
+    last: Option<usize>,
+
+    /// Write a Markdown changelog grouped by conventional commit type to this path
+    #[arg(long)]
+    changelog: Option<PathBuf>,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// リリース分析を実行して出力
+pub fn run(args: &ReleasesArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let git_repo = GitRepository::open(&commands::repository_path(&args.repo, settings))?;
+    let mut database = Database::open_existing(&commands::database_path(&args.db, settings))?
+        .with_flagged_commits(args.include_flagged);
+
+    let mut reports = releases::analyze_releases(&git_repo, &mut database)?;
+    if let Some(last) = args.last {
+        let skip = reports.len().saturating_sub(last);
+        reports.drain(..skip);
+    }
+
+    let rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|r| {
+            vec![
+                r.release.tag_name.clone(),
+                DateTime::from_timestamp(r.release.tag_date, 0)
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+                r.previous_tag.clone().unwrap_or_else(|| "-".to_string()),
+                r.stats.commits.to_string(),
+                r.stats.authors.to_string(),
+                r.stats.files_changed.to_string(),
+                format!("+{} -{}", r.stats.lines_added, r.stats.lines_deleted),
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &[
+            "RELEASE", "DATE", "SINCE", "COMMITS", "AUTHORS", "FILES", "CHURN",
+        ],
+        &rows,
+        &reports,
+    )?;
+
+    if let Some(path) = &args.changelog {
+        std::fs::write(path, releases::generate_changelog(&reports))?;
+        eprintln!("✓ Changelog written: {}", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use crate::commands;
+use clap::Args;
+use git_history::analysis::coupling::{self, CouplingOptions};
+use git_history::analysis::hotspots::{self, HotspotOptions};
+use git_history::analysis::rollups::TimeBucket;
+use git_history::analysis::timeline::{self, Timezone};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use git_history::git::GitRepository;
+use git_history::{GitHistoryError, Result};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// 同梱のダッシュボード（外部のスクリプトやフォントを読み込まないのでオフラインで動く）
+const DASHBOARD_HTML: &str = include_str!("serve/dashboard.html");
+
+/// `serve` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Repository path used to score hotspots by complexity [default: .]
+    #[arg(short, long)]
+    repo: Option<PathBuf>,
+
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Address to listen on (use 0.0.0.0 to allow access from other machines)
+    #[arg(long, default_value = "127.0.0.1")]
+    bind: String,
+
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+}
+
+/// HTTPサーバーを起動してダッシュボードとJSON APIを提供
+///
+/// DuckDBの接続はスレッド間で共有しないので、リクエストは1つずつ順に処理する。
+pub fn run(args: &ServeArgs, settings: &ConfigFile) -> Result<()> {
+    let db_path = commands::database_path(&args.db, settings);
+    let database = Database::open_existing(&db_path)?.with_flagged_commits(args.include_flagged);
+    let thresholds = &settings.thresholds;
+    let state = ServeState {
+        database,
+        db_path,
+        // ホットスポットの複雑度はリポジトリから読むので、開けなければその一覧だけエラーにする
+        git_repo: GitRepository::open(&commands::repository_path(&args.repo, settings))
+            .map_err(|e| e.to_string()),
+        coupling: CouplingOptions {
+            max_files_per_commit: thresholds.coupling_max_files.unwrap_or(50),
+            min_co_changes: thresholds.coupling_min_co_changes.unwrap_or(3),
+            ..CouplingOptions::default()
+        },
+        timezone: match &settings.analysis.timezone {
+            Some(timezone) => timezone.parse()?,
+            None => Timezone::Utc,
+        },
+    };
+
+    let address = format!("{}:{}", args.bind, args.port);
+    let server = Server::http(&address).map_err(|e| {
+        GitHistoryError::IoError(std::io::Error::other(format!(
+            "Cannot listen on {}: {}",
+            address, e
+        )))
+    })?;
+
+    println!(
+        "🌐 Serving {} at http://{}/",
+        state.db_path.display(),
+        address
+    );
+    if let Err(e) = &state.git_repo {
+        println!("⚠️  Hotspots are unavailable: {} (pass --repo)", e);
+    }
+    println!("   Press Ctrl-C to stop.");
+
+    for request in server.incoming_requests() {
+        let response = handle_request(&state, &request);
+        if let Err(e) = request.respond(response) {
+            eprintln!("⚠️  Failed to send response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// リクエストの処理に使う状態
+struct ServeState {
+    database: Database,
+    db_path: PathBuf,
+    /// 開けなかった場合はその理由
+    git_repo: std::result::Result<GitRepository, String>,
+    /// `/api/coupling` の集計条件（最小確信度はクエリで指定）
+    coupling: CouplingOptions,
+    /// `/api/timeline` の期間の区切りに使うタイムゾーン
+    timezone: Timezone,
+}
+
+/// リクエストを振り分けてレスポンスを作成
+fn handle_request(state: &ServeState, request: &Request) -> Response<Cursor<Vec<u8>>> {
+    if *request.method() != Method::Get {
+        return json_response(405, &json!({ "error": "Only GET is supported" }));
+    }
+
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path, parse_query(query)),
+        None => (request.url(), HashMap::new()),
+    };
+
+    if path == "/" || path == "/index.html" {
+        return Response::from_string(DASHBOARD_HTML)
+            .with_header(content_type("text/html; charset=utf-8"));
+    }
+
+    match api(state, path, &query) {
+        Ok(Some(body)) => json_response(200, &body),
+        Ok(None) => json_response(404, &json!({ "error": format!("Not found: {}", path) })),
+        // パラメータの誤りは400、それ以外はサーバー側のエラー
+        Err(e @ GitHistoryError::ConfigError(_)) => {
+            json_response(400, &json!({ "error": e.to_string() }))
+        }
+        Err(e) => json_response(500, &json!({ "error": e.to_string() })),
+    }
+}
+
+/// JSON APIのエンドポイント（該当しないパスはNone）
+fn api(state: &ServeState, path: &str, query: &HashMap<String, String>) -> Result<Option<Value>> {
+    let database = &state.database;
+    let body = match path {
+        "/api/summary" => json!({
+            "database": state.db_path.display().to_string(),
+            "total_commits": database.get_total_commits()?,
+            "total_files": database.get_total_files()?,
+            "latest_run": database.get_analysis_runs()?.into_iter().next(),
+        }),
+        "/api/hotspots" => {
+            let limit = param(query, "limit", 20)?;
+            let git_repo = state.git_repo.as_ref().map_err(|e| {
+                GitHistoryError::AnalysisError(format!("Hotspots need the repository: {}", e))
+            })?;
+            // hotspots テーブルは `hotspots` コマンドの結果なので、リクエストごとに計算する
+            let mut hotspots =
+                hotspots::compute_hotspots(git_repo, database, &HotspotOptions::default())?;
+            hotspots.truncate(limit);
+            list("hotspots", &hotspots)?
+        }
+        "/api/timeline" => {
+            let bucket: TimeBucket = param(query, "bucket", TimeBucket::Week)?;
+            // activity_timeline テーブルは analyze 時点の条件で作られるので、ビューから集計し直す
+            let activity = database.get_commit_activity()?;
+            list(
+                "activity_timeline",
+                &timeline::compute_activity(&activity, bucket, state.timezone),
+            )?
+        }
+        "/api/authors" => {
+            let limit = param(query, "limit", 50)?;
+            let mut authors = database.get_author_stats()?;
+            authors.truncate(limit);
+            list("authors", &authors)?
+        }
+        "/api/coupling" => {
+            let limit = param(query, "limit", 50)?;
+            let options = CouplingOptions {
+                min_confidence: param(query, "min_confidence", 0.5)?,
+                ..state.coupling.clone()
+            };
+            let mut couplings = coupling::compute_coupling(database, &options)?;
+            couplings.truncate(limit);
+            list("file_coupling", &couplings)?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(body))
+}
+
+/// `{"source": ..., "items": [...]}` 形式の一覧
+fn list<T: Serialize>(source: &str, items: &[T]) -> Result<Value> {
+    let items =
+        serde_json::to_value(items).map_err(|e| GitHistoryError::AnalysisError(e.to_string()))?;
+    Ok(json!({ "source": source, "items": items }))
+}
+
+/// クエリパラメータを取得（指定がなければ既定値）
+fn param<T: std::str::FromStr>(
+    query: &HashMap<String, String>,
+    name: &str,
+    default: T,
+) -> Result<T> {
+    match query.get(name) {
+        Some(value) => value.trim().parse().map_err(|_| {
+            GitHistoryError::ConfigError(format!("Invalid value for {}: {}", name, value))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// クエリ文字列を分解してキーと値をデコード
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+/// `%XX` と `+`（空白）をデコード（不正な `%` はそのまま残す）
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// JSONのレスポンス
+fn json_response(status: u16, body: &Value) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type("application/json"))
+}
+
+/// Content-Type ヘッダー
+fn content_type(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).expect("static header is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_values_are_percent_decoded() {
+        let query = parse_query("min_confidence=0.5%20&path=src%2Fmain.rs&name=a+b&bad=%zz%4");
+
+        assert_eq!(query["min_confidence"], "0.5 ");
+        assert_eq!(query["path"], "src/main.rs");
+        assert_eq!(query["name"], "a b");
+        assert_eq!(query["bad"], "%zz%4");
+        assert_eq!(param(&query, "min_confidence", 0.0).unwrap(), 0.5);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::snapshots::{self, SnapshotOptions};
+use git_history::config::file::ConfigFile;
+use git_history::database::{Database, TreeSnapshot};
+use git_history::git::GitRepository;
+use std::path::PathBuf;
+
+/// `snapshots` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct SnapshotsArgs {
+    /// Repository path [default: .]
+    #[arg(short, long)]
+    repo: Option<PathBuf>,
+
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Revision to walk back from (first-parent history)
+    #[arg(long, default_value = "HEAD")]
+    rev: String,
+
+    /// Sample a snapshot every N days along the first-parent history
+    #[arg(long, default_value_t = 30)]
+    every_days: u32,
+
+    /// Do not sample at regular intervals (tags only)
+    #[arg(long)]
+    no_interval: bool,
+
+    /// Do not sample at tags
+    #[arg(long)]
+    no_tags: bool,
+
+    /// Directory depth for the per-directory breakdown
+    #[arg(long, default_value_t = 1)]
+    depth: usize,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// スナップショットを取得して出力
+pub fn run(args: &SnapshotsArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let git_repo = GitRepository::open(&commands::repository_path(&args.repo, settings))?;
+    let mut database = Database::open_existing(&commands::database_path(&args.db, settings))?;
+    let options = SnapshotOptions {
+        revision: args.rev.clone(),
+        tags: !args.no_tags,
+        every_days: (!args.no_interval).then_some(args.every_days),
+        depth: args.depth,
+    };
+
+    let snapshots = snapshots::take_snapshots(&git_repo, &mut database, &options)?;
+
+    let totals: Vec<&TreeSnapshot> = snapshots
+        .iter()
+        .filter(|s| s.dimension == "total")
+        .collect();
+
+    let rows: Vec<Vec<String>> = totals
+        .iter()
+        .map(|total| {
+            // 行数の多い言語 上位3件
+            let mut languages: Vec<&TreeSnapshot> = snapshots
+                .iter()
+                .filter(|s| s.commit_hash == total.commit_hash && s.dimension == "language")
+                .collect();
+            languages.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.key.cmp(&b.key)));
+            let top_languages = languages
+                .iter()
+                .take(3)
+                .map(|s| format!("{} {}", s.key, s.lines))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            vec![
+                total.label.clone(),
+                total.commit_hash[..8].to_string(),
+                total.files.to_string(),
+                total.lines.to_string(),
+                top_languages,
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &["SNAPSHOT", "COMMIT", "FILES", "LINES", "TOP LANGUAGES"],
+        &rows,
+        &snapshots,
+    )
+}
@@ -0,0 +1,91 @@
+use crate::commands;
+use crate::commands::output::{self, OutputFormat};
+use clap::Args;
+use git_history::Result;
+use git_history::analysis::rollups::TimeBucket;
+use git_history::analysis::timeline::{self, Timezone};
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use std::path::PathBuf;
+
+/// 棒グラフの最大幅
+const BAR_WIDTH: usize = 30;
+
+/// `timeline` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct TimelineArgs {
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Time bucket (day, week, month)
+    #[arg(short, long, default_value = "week")]
+    bucket: TimeBucket,
+
+    /// Timezone used to assign commits to buckets (utc, author, or a fixed offset like +09:00; zone names such as Europe/Berlin are not supported, so DST is not applied) [default: utc]
+    #[arg(long)]
+    timezone: Option<Timezone>,
+
+    /// Only show the most recent N buckets
+    #[arg(short = 'n', long)]
+    last: Option<usize>,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+
+    /// Output format [default: table]
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+/// 時系列の活動量を出力
+pub fn run(args: &TimelineArgs, settings: &ConfigFile) -> Result<()> {
+    let format = output::resolve_format(args.format, settings);
+    let mut database = Database::open_existing(&commands::database_path(&args.db, settings))?
+        .with_flagged_commits(args.include_flagged);
+    let timezone = match (args.timezone, &settings.analysis.timezone) {
+        (Some(timezone), _) => timezone,
+        (None, Some(timezone)) => timezone.parse()?,
+        (None, None) => Timezone::Utc,
+    };
+    timeline::refresh_activity_timeline(&mut database, timezone)?;
+
+    let mut buckets = database.get_activity_timeline(args.bucket)?;
+    if let Some(last) = args.last {
+        let skip = buckets.len().saturating_sub(last);
+        buckets.drain(..skip);
+    }
+
+    let max_commits = buckets.iter().map(|b| b.commits).max().unwrap_or(0).max(1);
+    let rows: Vec<Vec<String>> = buckets
+        .iter()
+        .map(|b| {
+            let bar_len = (b.commits as usize * BAR_WIDTH).div_ceil(max_commits as usize);
+            vec![
+                b.bucket_start.clone(),
+                b.commits.to_string(),
+                b.active_authors.to_string(),
+                b.lines_added.to_string(),
+                b.lines_deleted.to_string(),
+                b.new_files.to_string(),
+                "█".repeat(bar_len),
+            ]
+        })
+        .collect();
+
+    output::print_report(
+        format,
+        &[
+            "PERIOD",
+            "COMMITS",
+            "AUTHORS",
+            "ADDED",
+            "DELETED",
+            "NEW FILES",
+            "ACTIVITY",
+        ],
+        &rows,
+        &buckets,
+    )
+}
@@ -0,0 +1,98 @@
+mod app;
+mod ui;
+
+use crate::commands;
+use app::{App, Tab};
+use clap::Args;
+use git_history::Result;
+use git_history::config::file::ConfigFile;
+use git_history::database::Database;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::path::PathBuf;
+
+/// PageUp / PageDown で移動する行数
+const PAGE: isize = 20;
+
+/// `tui` サブコマンドの引数
+#[derive(Args, Debug)]
+pub struct TuiArgs {
+    /// Database path produced by `analyze` [default: git-history.db]
+    #[arg(short, long)]
+    db: Option<PathBuf>,
+
+    /// Include bulk, formatting and rename-only commits tagged in commit_kinds
+    #[arg(long)]
+    include_flagged: bool,
+}
+
+/// ターミナルUIでファイル・コミット・作成者を閲覧
+pub fn run(args: &TuiArgs, settings: &ConfigFile) -> Result<()> {
+    let db_path = commands::database_path(&args.db, settings);
+    let database = Database::open_existing(&db_path)?.with_flagged_commits(args.include_flagged);
+    let mut app = App::new(&database)?;
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    // エラーでも端末の状態は必ず元に戻す
+    ratatui::restore();
+    result
+}
+
+/// 描画とキー入力の処理を終了するまで繰り返す
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        // データベースのエラーは終了せずステータス行に表示
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && let Err(e) = handle_key(app, key)
+        {
+            app.status = Some(format!("❌ {}", e));
+        }
+    }
+    Ok(())
+}
+
+/// キー入力を処理
+fn handle_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        app.quit = true;
+        return Ok(());
+    }
+
+    // 検索文字列の入力中
+    if let Some(input) = app.input.as_mut() {
+        match key.code {
+            KeyCode::Enter => app.apply_search()?,
+            KeyCode::Esc => app.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    app.status = None;
+    match key.code {
+        KeyCode::Char('q') => app.quit = true,
+        KeyCode::Tab => app.next_tab(1)?,
+        KeyCode::BackTab => app.next_tab(-1)?,
+        KeyCode::Char('1') => app.switch_tab(Tab::Files)?,
+        KeyCode::Char('2') => app.switch_tab(Tab::Commits)?,
+        KeyCode::Char('3') => app.switch_tab(Tab::Authors)?,
+        KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
+        KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1),
+        KeyCode::PageDown => app.move_selection(PAGE),
+        KeyCode::PageUp => app.move_selection(-PAGE),
+        KeyCode::Home | KeyCode::Char('g') => app.select_edge(false),
+        KeyCode::End | KeyCode::Char('G') => app.select_edge(true),
+        KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => app.open_selected()?,
+        KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => app.back(),
+        KeyCode::Char('/') => app.start_search(),
+        _ => {}
+    }
+    Ok(())
+}
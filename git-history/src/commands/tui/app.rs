@@ -0,0 +1,406 @@
+use chrono::{DateTime, Datelike};
+use git_history::Result;
+use git_history::database::{
+    AuthorStats, CommitFile, CommitSummary, Database, FileChangeStats, FileCommit,
+};
+use std::collections::BTreeMap;
+
+/// コミット一覧に読み込む最大件数（それより古いコミットは検索で絞り込む）
+pub const COMMIT_LIMIT: usize = 1000;
+
+/// タブ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Files,
+    Commits,
+    Authors,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 3] = [Tab::Files, Tab::Commits, Tab::Authors];
+
+    /// 表示名
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Files => "Files",
+            Tab::Commits => "Commits",
+            Tab::Authors => "Authors",
+        }
+    }
+}
+
+/// ディレクトリ内の項目（サブディレクトリまたはファイル）
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub change_count: i64,
+    pub lines_changed: i64,
+}
+
+/// 画面の種類と表示するデータ
+pub enum View {
+    Directory {
+        path: String,
+        entries: Vec<DirEntry>,
+    },
+    File {
+        path: String,
+        commits: Vec<FileCommit>,
+        /// 月ごとのチャーン（最初の変更から最後の変更まで、変更のない月は0）
+        churn: Vec<u64>,
+        /// チャーンの期間（`YYYY-MM`）
+        churn_range: Option<(String, String)>,
+    },
+    Commits {
+        title: String,
+        author_email: Option<String>,
+        commits: Vec<CommitSummary>,
+    },
+    Commit {
+        commit: CommitSummary,
+        files: Vec<CommitFile>,
+    },
+    Authors {
+        authors: Vec<AuthorStats>,
+    },
+}
+
+/// ナビゲーションの1階層（画面・選択位置・絞り込み）
+pub struct Screen {
+    pub view: View,
+    pub selected: usize,
+    pub filter: String,
+}
+
+impl Screen {
+    fn new(view: View) -> Self {
+        Self {
+            view,
+            selected: 0,
+            filter: String::new(),
+        }
+    }
+
+    /// 絞り込みに一致する項目の添字
+    ///
+    /// コミット一覧はデータベース側で検索するので、ここでは絞り込まない。
+    pub fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        let matches = |texts: &[&str]| {
+            filter.is_empty() || texts.iter().any(|t| t.to_lowercase().contains(&filter))
+        };
+
+        let indices = |len: usize, f: &dyn Fn(usize) -> bool| (0..len).filter(|&i| f(i)).collect();
+        match &self.view {
+            View::Directory { entries, .. } => {
+                indices(entries.len(), &|i| matches(&[&entries[i].name]))
+            }
+            View::File { commits, .. } => indices(commits.len(), &|i| {
+                let c = &commits[i];
+                matches(&[&c.message, &c.author_name, &c.author_email, &c.commit_hash])
+            }),
+            View::Commits { commits, .. } => (0..commits.len()).collect(),
+            View::Commit { files, .. } => {
+                indices(files.len(), &|i| matches(&[&files[i].file_path]))
+            }
+            View::Authors { authors, .. } => indices(authors.len(), &|i| {
+                matches(&[&authors[i].author_name, &authors[i].author_email])
+            }),
+        }
+    }
+
+    /// 画面のタイトル（パンくず）
+    pub fn title(&self) -> String {
+        match &self.view {
+            View::Directory { path, .. } if path.is_empty() => "/".to_string(),
+            View::Directory { path, .. } => format!("{}/", path),
+            View::File { path, .. } => path.clone(),
+            View::Commits { title, .. } => title.clone(),
+            View::Commit { commit, .. } => format!("commit {}", short_hash(&commit.commit_hash)),
+            View::Authors { .. } => "Authors".to_string(),
+        }
+    }
+}
+
+/// 選択した項目の開き先
+enum Target {
+    Directory(String),
+    File(String),
+    Commit(String),
+    /// (名前, メールアドレス)
+    Author(String, String),
+}
+
+/// アプリケーションの状態
+pub struct App<'a> {
+    database: &'a Database,
+    /// 全ファイルの変更回数（ディレクトリ画面の集計に使う）
+    files: Vec<FileChangeStats>,
+    pub tab: Tab,
+    pub stack: Vec<Screen>,
+    /// 入力中の検索文字列（`/` で開始）
+    pub input: Option<String>,
+    /// ステータス行のメッセージ
+    pub status: Option<String>,
+    pub quit: bool,
+}
+
+impl<'a> App<'a> {
+    /// データベースを読み込んでファイル一覧の画面から開始
+    pub fn new(database: &'a Database) -> Result<Self> {
+        let mut app = Self {
+            database,
+            files: database.get_file_change_stats()?,
+            tab: Tab::Files,
+            stack: Vec::new(),
+            input: None,
+            status: None,
+            quit: false,
+        };
+        app.switch_tab(Tab::Files)?;
+        Ok(app)
+    }
+
+    /// 表示中の画面
+    pub fn screen(&self) -> &Screen {
+        self.stack.last().expect("navigation stack is never empty")
+    }
+
+    fn screen_mut(&mut self) -> &mut Screen {
+        self.stack
+            .last_mut()
+            .expect("navigation stack is never empty")
+    }
+
+    /// タブを切り替え（ナビゲーションはタブの最上位に戻る）
+    pub fn switch_tab(&mut self, tab: Tab) -> Result<()> {
+        let view = match tab {
+            Tab::Files => self.directory_view(""),
+            Tab::Commits => View::Commits {
+                title: "Commits".to_string(),
+                author_email: None,
+                commits: self
+                    .database
+                    .get_commit_summaries(None, None, COMMIT_LIMIT)?,
+            },
+            Tab::Authors => View::Authors {
+                authors: self.database.get_author_stats()?,
+            },
+        };
+        self.tab = tab;
+        self.stack = vec![Screen::new(view)];
+        Ok(())
+    }
+
+    /// 次のタブへ
+    pub fn next_tab(&mut self, step: isize) -> Result<()> {
+        let index = Tab::ALL.iter().position(|t| *t == self.tab).unwrap_or(0) as isize;
+        let next = (index + step).rem_euclid(Tab::ALL.len() as isize) as usize;
+        self.switch_tab(Tab::ALL[next])
+    }
+
+    /// 選択位置を移動
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.screen().visible().len();
+        let screen = self.screen_mut();
+        screen.selected = if len == 0 {
+            0
+        } else {
+            (screen.selected as isize + delta).clamp(0, len as isize - 1) as usize
+        };
+    }
+
+    /// 選択位置を先頭または末尾へ
+    pub fn select_edge(&mut self, last: bool) {
+        let len = self.screen().visible().len();
+        self.screen_mut().selected = if last { len.saturating_sub(1) } else { 0 };
+    }
+
+    /// 選択した項目を開く（ディレクトリ → ファイル → コミット、作成者 → コミット一覧）
+    pub fn open_selected(&mut self) -> Result<()> {
+        let screen = self.screen();
+        let Some(&index) = screen.visible().get(screen.selected) else {
+            return Ok(());
+        };
+
+        let target = match &screen.view {
+            View::Directory { entries, .. } if entries[index].is_dir => {
+                Target::Directory(entries[index].path.clone())
+            }
+            View::Directory { entries, .. } => Target::File(entries[index].path.clone()),
+            View::File { commits, .. } => Target::Commit(commits[index].commit_hash.clone()),
+            View::Commits { commits, .. } => Target::Commit(commits[index].commit_hash.clone()),
+            View::Commit { files, .. } => Target::File(files[index].file_path.clone()),
+            View::Authors { authors } => Target::Author(
+                authors[index].author_name.clone(),
+                authors[index].author_email.clone(),
+            ),
+        };
+
+        let view = match target {
+            Target::Directory(path) => self.directory_view(&path),
+            Target::File(path) => self.file_view(&path)?,
+            Target::Commit(hash) => match self.commit_view(&hash)? {
+                Some(view) => view,
+                None => return Ok(()),
+            },
+            Target::Author(name, email) => View::Commits {
+                title: format!("{} <{}>", name, email),
+                commits: self
+                    .database
+                    .get_commit_summaries(None, Some(&email), COMMIT_LIMIT)?,
+                author_email: Some(email),
+            },
+        };
+
+        self.stack.push(Screen::new(view));
+        Ok(())
+    }
+
+    /// 1つ前の画面に戻る
+    pub fn back(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    /// 検索の入力を開始
+    pub fn start_search(&mut self) {
+        self.input = Some(self.screen().filter.clone());
+    }
+
+    /// 入力した検索文字列を適用（コミット一覧はデータベースを検索し直す）
+    pub fn apply_search(&mut self) -> Result<()> {
+        let Some(query) = self.input.take() else {
+            return Ok(());
+        };
+        let query = query.trim().to_string();
+
+        let database = self.database;
+        let screen = self.screen_mut();
+        if let View::Commits {
+            author_email,
+            commits,
+            ..
+        } = &mut screen.view
+        {
+            let search = (!query.is_empty()).then_some(query.as_str());
+            *commits =
+                database.get_commit_summaries(search, author_email.as_deref(), COMMIT_LIMIT)?;
+        }
+        screen.filter = query;
+        screen.selected = 0;
+        Ok(())
+    }
+
+    /// ディレクトリ直下の項目を変更回数の多い順に集計
+    fn directory_view(&self, path: &str) -> View {
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+
+        let mut children: BTreeMap<&str, DirEntry> = BTreeMap::new();
+        for file in &self.files {
+            let Some(rest) = file.file_path.strip_prefix(&prefix) else {
+                continue;
+            };
+            let (name, is_dir) = match rest.split_once('/') {
+                Some((dir, _)) => (dir, true),
+                None => (rest, false),
+            };
+            let entry = children.entry(name).or_insert_with(|| DirEntry {
+                name: name.to_string(),
+                path: format!("{}{}", prefix, name),
+                is_dir,
+                change_count: 0,
+                lines_changed: 0,
+            });
+            entry.change_count += file.change_count;
+            entry.lines_changed += file.lines_changed;
+        }
+
+        let mut entries: Vec<DirEntry> = children.into_values().collect();
+        entries.sort_by(|a, b| {
+            b.change_count
+                .cmp(&a.change_count)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        View::Directory {
+            path: path.to_string(),
+            entries,
+        }
+    }
+
+    /// ファイルの履歴と月ごとのチャーン
+    fn file_view(&self, path: &str) -> Result<View> {
+        let commits = self.database.get_file_commits(path)?;
+        let (churn, churn_range) = monthly_churn(&commits);
+        Ok(View::File {
+            path: path.to_string(),
+            commits,
+            churn,
+            churn_range,
+        })
+    }
+
+    /// コミットの詳細（見つからない場合はステータス行に表示してNone）
+    fn commit_view(&mut self, commit_hash: &str) -> Result<Option<View>> {
+        let commit = self
+            .database
+            .get_commit_summaries(Some(commit_hash), None, COMMIT_LIMIT)?
+            .into_iter()
+            .find(|c| c.commit_hash == commit_hash);
+        let Some(commit) = commit else {
+            self.status = Some(format!("Commit {} not found", short_hash(commit_hash)));
+            return Ok(None);
+        };
+
+        let files = self.database.get_commit_files(commit_hash)?;
+        Ok(Some(View::Commit { commit, files }))
+    }
+}
+
+/// 月ごとのチャーン（古い順）と期間
+fn monthly_churn(commits: &[FileCommit]) -> (Vec<u64>, Option<(String, String)>) {
+    let month = |timestamp: i64| {
+        DateTime::from_timestamp(timestamp, 0).map(|d| d.year() * 12 + d.month0() as i32)
+    };
+
+    let mut by_month: BTreeMap<i32, u64> = BTreeMap::new();
+    for commit in commits {
+        if let Some(month) = month(commit.commit_date) {
+            *by_month.entry(month).or_default() +=
+                (commit.lines_added + commit.lines_deleted).max(0) as u64;
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (by_month.keys().next(), by_month.keys().next_back()) else {
+        return (Vec::new(), None);
+    };
+    let churn = (first..=last)
+        .map(|m| by_month.get(&m).copied().unwrap_or(0))
+        .collect();
+    let label = |m: i32| format!("{:04}-{:02}", m.div_euclid(12), m.rem_euclid(12) + 1);
+    (churn, Some((label(first), label(last))))
+}
+
+/// 短縮したコミットハッシュ
+pub fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(8)]
+}
+
+/// Unix timestamp を `YYYY-MM-DD` に変換
+pub fn date(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// コミットメッセージの1行目
+pub fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("")
+}
@@ -0,0 +1,254 @@
+use super::app::{self, App, Screen, Tab, View};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, Borders, Cell, Paragraph, Row, Sparkline, Table, TableState, Tabs, Wrap,
+};
+
+/// 選択中の行のスタイル
+const HIGHLIGHT: Style = Style::new().add_modifier(Modifier::REVERSED);
+
+/// 画面全体を描画
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [tabs_area, main_area, footer_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let selected_tab = Tab::ALL.iter().position(|t| *t == app.tab).unwrap_or(0);
+    let tabs = Tabs::new(Tab::ALL.iter().map(|t| t.title()))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" git-history "),
+        )
+        .select(selected_tab)
+        .highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    frame.render_widget(tabs, tabs_area);
+
+    draw_screen(frame, app.screen(), main_area);
+    draw_footer(frame, app, footer_area);
+}
+
+/// 表示中の画面を描画
+fn draw_screen(frame: &mut Frame, screen: &Screen, area: Rect) {
+    let visible = screen.visible();
+    let mut title = format!(" {} ", screen.title());
+    if !screen.filter.is_empty() {
+        title.push_str(&format!("[/{}] ", screen.filter));
+    }
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    let (headers, widths, rows, area): (Vec<&str>, Vec<Constraint>, Vec<Row>, Rect) =
+        match &screen.view {
+            View::Directory { entries, .. } => (
+                vec!["NAME", "CHANGES", "CHURN"],
+                vec![
+                    Constraint::Min(30),
+                    Constraint::Length(8),
+                    Constraint::Length(10),
+                ],
+                visible
+                    .iter()
+                    .map(|&i| {
+                        let e = &entries[i];
+                        let name = if e.is_dir {
+                            format!("{}/", e.name)
+                        } else {
+                            e.name.clone()
+                        };
+                        Row::new(vec![
+                            Cell::from(name).style(if e.is_dir {
+                                Style::new().fg(Color::Cyan)
+                            } else {
+                                Style::new()
+                            }),
+                            Cell::from(e.change_count.to_string()),
+                            Cell::from(e.lines_changed.to_string()),
+                        ])
+                    })
+                    .collect(),
+                area,
+            ),
+            View::File {
+                commits,
+                churn,
+                churn_range,
+                ..
+            } => {
+                let [chart_area, table_area] =
+                    Layout::vertical([Constraint::Length(7), Constraint::Min(0)]).areas(area);
+                let chart_title = match churn_range {
+                    Some((first, last)) => format!(" Monthly churn {}..{} ", first, last),
+                    None => " Monthly churn ".to_string(),
+                };
+                let sparkline = Sparkline::default()
+                    .block(Block::default().borders(Borders::ALL).title(chart_title))
+                    .data(churn)
+                    .style(Style::new().fg(Color::Green));
+                frame.render_widget(sparkline, chart_area);
+
+                (
+                    vec!["DATE", "COMMIT", "AUTHOR", "TYPE", "+", "-", "SUBJECT"],
+                    vec![
+                        Constraint::Length(10),
+                        Constraint::Length(8),
+                        Constraint::Length(20),
+                        Constraint::Length(11),
+                        Constraint::Length(6),
+                        Constraint::Length(6),
+                        Constraint::Min(20),
+                    ],
+                    visible
+                        .iter()
+                        .map(|&i| {
+                            let c = &commits[i];
+                            Row::new(vec![
+                                app::date(c.commit_date),
+                                app::short_hash(&c.commit_hash).to_string(),
+                                c.author_name.clone(),
+                                c.change_type.clone(),
+                                c.lines_added.to_string(),
+                                c.lines_deleted.to_string(),
+                                app::subject(&c.message).to_string(),
+                            ])
+                        })
+                        .collect(),
+                    table_area,
+                )
+            }
+            View::Commits { commits, .. } => (
+                vec!["DATE", "COMMIT", "AUTHOR", "FILES", "+", "-", "SUBJECT"],
+                vec![
+                    Constraint::Length(10),
+                    Constraint::Length(8),
+                    Constraint::Length(20),
+                    Constraint::Length(5),
+                    Constraint::Length(6),
+                    Constraint::Length(6),
+                    Constraint::Min(20),
+                ],
+                visible
+                    .iter()
+                    .map(|&i| {
+                        let c = &commits[i];
+                        Row::new(vec![
+                            app::date(c.commit_date),
+                            app::short_hash(&c.commit_hash).to_string(),
+                            c.author_name.clone(),
+                            c.files_changed.to_string(),
+                            c.lines_added.to_string(),
+                            c.lines_deleted.to_string(),
+                            app::subject(&c.message).to_string(),
+                        ])
+                    })
+                    .collect(),
+                area,
+            ),
+            View::Commit { commit, files } => {
+                let message_lines = commit.message.trim_end().lines().count() as u16;
+                let [detail_area, table_area] = Layout::vertical([
+                    Constraint::Length((message_lines + 4).min(area.height / 2)),
+                    Constraint::Min(0),
+                ])
+                .areas(area);
+                let mut text = vec![
+                    Line::from(format!("commit {}", commit.commit_hash)),
+                    Line::from(format!(
+                        "Author: {} <{}>  Date: {}",
+                        commit.author_name,
+                        commit.author_email,
+                        app::date(commit.commit_date)
+                    )),
+                ];
+                text.extend(
+                    commit
+                        .message
+                        .trim_end()
+                        .lines()
+                        .map(|l| Line::from(format!("  {}", l))),
+                );
+                let detail = Paragraph::new(text)
+                    .block(Block::default().borders(Borders::ALL))
+                    .wrap(Wrap { trim: false });
+                frame.render_widget(detail, detail_area);
+
+                (
+                    vec!["TYPE", "+", "-", "PATH"],
+                    vec![
+                        Constraint::Length(11),
+                        Constraint::Length(6),
+                        Constraint::Length(6),
+                        Constraint::Min(20),
+                    ],
+                    visible
+                        .iter()
+                        .map(|&i| {
+                            let f = &files[i];
+                            Row::new(vec![
+                                f.change_type.clone(),
+                                f.lines_added.to_string(),
+                                f.lines_deleted.to_string(),
+                                f.file_path.clone(),
+                            ])
+                        })
+                        .collect(),
+                    table_area,
+                )
+            }
+            View::Authors { authors } => (
+                vec!["AUTHOR", "EMAIL", "COMMITS", "+", "-", "FIRST", "LAST"],
+                vec![
+                    Constraint::Length(24),
+                    Constraint::Min(24),
+                    Constraint::Length(7),
+                    Constraint::Length(8),
+                    Constraint::Length(8),
+                    Constraint::Length(10),
+                    Constraint::Length(10),
+                ],
+                visible
+                    .iter()
+                    .map(|&i| {
+                        let a = &authors[i];
+                        Row::new(vec![
+                            a.author_name.clone(),
+                            a.author_email.clone(),
+                            a.commits.to_string(),
+                            a.lines_added.to_string(),
+                            a.lines_deleted.to_string(),
+                            app::date(a.first_commit_date),
+                            app::date(a.last_commit_date),
+                        ])
+                    })
+                    .collect(),
+                area,
+            ),
+        };
+
+    let table = Table::new(rows, widths)
+        .header(Row::new(headers).style(Style::new().add_modifier(Modifier::BOLD)))
+        .block(block)
+        .row_highlight_style(HIGHLIGHT);
+    let mut state = TableState::default().with_selected(Some(screen.selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// 検索の入力欄、ステータス、キー操作の説明を描画
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match (&app.input, &app.status) {
+        (Some(input), _) => format!("/{}▏ (Enter: apply, Esc: cancel)", input),
+        (None, Some(status)) => status.clone(),
+        (None, None) => {
+            "q: quit  Tab: switch view  ↑↓: move  Enter: open  Esc: back  /: search".to_string()
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(text).style(Style::new().fg(Color::DarkGray)),
+        area,
+    );
+}
@@ -1,3 +1,14 @@
+pub mod file;
+pub mod filters;
+
+use crate::analysis::commit_kinds::CommitKindOptions;
+use crate::analysis::rollups::RollupOptions;
+use crate::analysis::timeline::Timezone;
+use crate::error::{GitHistoryError, Result};
+use crate::git::remote::MirrorOptions;
+use crate::git::{CommitOrder, CommitSelection, DiffStatOptions};
+use chrono::{DateTime, NaiveDate};
+use filters::{AuthorAliases, PathFilter};
 use std::path::PathBuf;
 
 /// アプリケーション設定
@@ -9,17 +20,65 @@ pub struct Config {
     /// 出力データベースのパス
     pub output_db: PathBuf,
 
+    /// クローンURL（指定時は `repo_path` の代わりにミラーキャッシュを解析）
+    pub remote_url: Option<String>,
+
+    /// ミラーキャッシュの設定
+    pub mirror: MirrorOptions,
+
     /// 解析対象ブランチ（Noneの場合は全ブランチ）
     pub branch: Option<String>,
 
     /// 増分更新モード
     pub incremental: bool,
 
+    /// 増分更新時に到達不能になったコミットを削除する
+    pub prune_orphans: bool,
+
+    /// 中断した解析をチェックポイントから再開する
+    pub resume: bool,
+
+    /// コミット単位の失敗を analysis_errors に記録して解析を続ける
+    pub keep_going: bool,
+
     /// 詳細ログ
     pub verbose: bool,
 
     /// コミット数の上限
     pub limit: Option<usize>,
+
+    /// リビジョン範囲（`v1.0..v2.0`, `main...feature`）
+    pub revision_range: Option<String>,
+
+    /// この日時以降のコミットのみ (Unix timestamp)
+    pub since: Option<i64>,
+
+    /// この日時以前のコミットのみ (Unix timestamp)
+    pub until: Option<i64>,
+
+    /// 最初の親のみを辿る
+    pub first_parent: bool,
+
+    /// コミットの並び順
+    pub order: CommitOrder,
+
+    /// 解析から除外するファイルパス
+    pub path_filter: PathFilter,
+
+    /// 作成者の別名
+    pub author_aliases: AuthorAliases,
+
+    /// 追加・削除行数を数える差分の設定
+    pub diff: DiffStatOptions,
+
+    /// 一括変更・整形コミット（commit_kinds）の判定基準
+    pub commit_kinds: CommitKindOptions,
+
+    /// ディレクトリ集計（dir_rollups）の設定
+    pub rollups: RollupOptions,
+
+    /// 時系列集計（activity_timeline）のタイムゾーン
+    pub timezone: Timezone,
 }
 
 impl Config {
@@ -28,13 +87,41 @@ impl Config {
         Self {
             repo_path,
             output_db,
+            remote_url: None,
+            mirror: MirrorOptions::default(),
             branch: None,
             incremental: false,
+            prune_orphans: false,
+            resume: true,
+            keep_going: false,
             verbose: false,
             limit: None,
+            revision_range: None,
+            since: None,
+            until: None,
+            first_parent: false,
+            order: CommitOrder::Time,
+            path_filter: PathFilter::default(),
+            author_aliases: AuthorAliases::default(),
+            diff: DiffStatOptions::default(),
+            commit_kinds: CommitKindOptions::default(),
+            rollups: RollupOptions::default(),
+            timezone: Timezone::Utc,
         }
     }
 
+    /// クローンURLを設定
+    pub fn with_remote(mut self, remote_url: Option<String>) -> Self {
+        self.remote_url = remote_url;
+        self
+    }
+
+    /// ミラーキャッシュの設定を指定
+    pub fn with_mirror(mut self, mirror: MirrorOptions) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
     /// ブランチを設定
     pub fn with_branch(mut self, branch: Option<String>) -> Self {
         self.branch = branch;
@@ -47,6 +134,24 @@ impl Config {
         self
     }
 
+    /// 孤立したコミットの削除を設定
+    pub fn with_prune_orphans(mut self, prune_orphans: bool) -> Self {
+        self.prune_orphans = prune_orphans;
+        self
+    }
+
+    /// チェックポイントからの再開を設定
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// コミット単位の失敗で解析を止めないかを設定
+    pub fn with_keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
     /// 詳細ログを設定
     pub fn with_verbose(mut self, verbose: bool) -> Self {
         self.verbose = verbose;
@@ -58,4 +163,108 @@ impl Config {
         self.limit = limit;
         self
     }
+
+    /// リビジョン範囲を設定
+    pub fn with_revision_range(mut self, revision_range: Option<String>) -> Self {
+        self.revision_range = revision_range;
+        self
+    }
+
+    /// 日時の範囲を設定
+    pub fn with_date_range(mut self, since: Option<i64>, until: Option<i64>) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    /// first-parentモードを設定
+    pub fn with_first_parent(mut self, first_parent: bool) -> Self {
+        self.first_parent = first_parent;
+        self
+    }
+
+    /// コミットの並び順を設定
+    pub fn with_order(mut self, order: CommitOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// 除外するファイルパスを設定
+    pub fn with_path_filter(mut self, path_filter: PathFilter) -> Self {
+        self.path_filter = path_filter;
+        self
+    }
+
+    /// 作成者の別名を設定
+    pub fn with_author_aliases(mut self, author_aliases: AuthorAliases) -> Self {
+        self.author_aliases = author_aliases;
+        self
+    }
+
+    /// 差分の設定（アルゴリズム・コンテキスト行数・空白の無視）を指定
+    pub fn with_diff_options(mut self, diff: DiffStatOptions) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// 一括変更・整形コミットの判定基準を設定
+    pub fn with_commit_kinds(mut self, commit_kinds: CommitKindOptions) -> Self {
+        self.commit_kinds = commit_kinds;
+        self
+    }
+
+    /// コミットの選択条件を取得
+    pub fn commit_selection(&self) -> CommitSelection {
+        CommitSelection {
+            branch: self.branch.clone(),
+            range: self.revision_range.clone(),
+            since: self.since,
+            until: self.until,
+            first_parent: self.first_parent,
+            order: self.order,
+            limit: self.limit,
+        }
+    }
+
+    /// ディレクトリ集計の設定
+    pub fn with_rollups(mut self, rollups: RollupOptions) -> Self {
+        self.rollups = rollups;
+        self
+    }
+
+    /// 時系列集計のタイムゾーンを設定
+    pub fn with_timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+/// 日時の文字列をUnix timestampに変換
+///
+/// `YYYY-MM-DD`（UTC、`end_of_day` がtrueの場合はその日の終わり）、RFC 3339、
+/// Unix timestampの数値を受け付ける。
+pub fn parse_date(value: &str, end_of_day: bool) -> Result<i64> {
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Ok(timestamp);
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.timestamp());
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        if let Some(time) = time {
+            return Ok(time.and_utc().timestamp());
+        }
+    }
+
+    Err(GitHistoryError::ConfigError(format!(
+        "Invalid date: {} (expected YYYY-MM-DD, RFC 3339 or a Unix timestamp)",
+        value
+    )))
 }
@@ -0,0 +1,573 @@
+use crate::analysis::commit_kinds::CommitKindOptions;
+use crate::analysis::rollups::{RollupOptions, TimeBucket};
+use crate::analysis::timeline::Timezone;
+use crate::config::filters::{AuthorAliases, PathFilter};
+use crate::config::{Config, parse_date};
+use crate::error::{GitHistoryError, Result};
+use crate::git::remote::{self, MirrorOptions};
+use crate::git::{CommitOrder, DiffAlgorithm, DiffStatOptions, IgnoreWhitespace};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 設定ファイル名
+pub const CONFIG_FILE_NAME: &str = "git-history.toml";
+
+/// `git-history.toml` の内容
+///
+/// 読み込み順は ユーザー設定 → プロジェクト設定 で、後に読んだ値が優先される。
+/// CLIで指定した値はさらにその上に適用する。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// 解析対象のリポジトリ
+    pub repository: RepositorySection,
+    /// 解析対象のコミット
+    pub refs: RefsSection,
+    /// 期間・件数・パスの絞り込み
+    pub filters: FiltersSection,
+    /// 作成者の別名
+    pub authors: AuthorsSection,
+    /// 出力先と形式
+    pub output: OutputSection,
+    /// 解析と集計の設定
+    pub analysis: AnalysisSection,
+    /// レポートのしきい値
+    pub thresholds: ThresholdsSection,
+}
+
+/// `[repository]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepositorySection {
+    /// リポジトリのパスまたはクローンURL
+    pub path: Option<String>,
+    /// ミラーキャッシュのディレクトリ
+    pub mirror_dir: Option<PathBuf>,
+    /// ミラー取得時の深さ
+    pub depth: Option<u32>,
+    /// ミラー取得時の参照
+    pub fetch_refs: Option<Vec<String>>,
+}
+
+/// `[refs]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefsSection {
+    pub branch: Option<String>,
+    pub range: Option<String>,
+    pub first_parent: Option<bool>,
+    /// `time`, `topo`, `reverse`
+    pub order: Option<String>,
+}
+
+/// `[filters]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FiltersSection {
+    /// `YYYY-MM-DD`, RFC 3339 または Unix timestamp（文字列）
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+    /// 解析から除外するパスのglob
+    pub exclude_paths: Option<Vec<String>>,
+}
+
+/// `[authors]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthorsSection {
+    /// メールアドレスまたは名前 → `"Name <email>"`
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// `[output]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSection {
+    /// データベースのパス
+    pub database: Option<PathBuf>,
+    /// レポートの出力形式（`table`, `csv`, `json`）
+    pub format: Option<String>,
+}
+
+/// `[analysis]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnalysisSection {
+    pub incremental: Option<bool>,
+    pub prune_orphans: Option<bool>,
+    pub keep_going: Option<bool>,
+    pub rollup_depth: Option<usize>,
+    /// `day`, `week`, `month`
+    pub rollup_bucket: Option<String>,
+    /// `utc`, `author`, `+09:00`
+    pub timezone: Option<String>,
+    /// `myers`, `patience`, `minimal`
+    pub diff_algorithm: Option<String>,
+    pub diff_context_lines: Option<u32>,
+    /// `eol`, `change`, `all`（指定時は空白の変更を無視した行数も記録）
+    pub ignore_whitespace: Option<String>,
+    pub detect_renames: Option<bool>,
+}
+
+/// `[thresholds]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdsSection {
+    pub coupling_max_files: Option<usize>,
+    pub coupling_min_co_changes: Option<usize>,
+    pub coupling_min_confidence: Option<f64>,
+    pub ownership_half_life_days: Option<f64>,
+    pub ownership_inactive_days: Option<i64>,
+    /// これより多くのファイルを変更したコミットを `BULK` とする
+    pub bulk_commit_files: Option<usize>,
+    /// 変更行のうち空白のみの変更がこの割合以上のコミットを `FORMATTING` とする
+    pub formatting_whitespace_share: Option<f64>,
+}
+
+/// レポートの出力形式として受け付ける値
+const OUTPUT_FORMATS: [&str; 3] = ["table", "csv", "json"];
+
+impl ConfigFile {
+    /// ファイルを読み込んで検証
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            GitHistoryError::ConfigError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let file: ConfigFile = toml::from_str(&content).map_err(|e| {
+            GitHistoryError::ConfigError(format!("{}: {}", path.display(), e.message()))
+        })?;
+        file.validate()
+            .map_err(|e| GitHistoryError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        Ok(file)
+    }
+
+    /// ユーザー設定とプロジェクト設定を読み込んでマージ
+    ///
+    /// プロジェクト設定は `project` が指定されればそのファイル（存在しなければエラー）、
+    /// なければカレントディレクトリの `git-history.toml`（存在する場合のみ）。
+    pub fn discover(project: Option<&Path>) -> Result<Self> {
+        let mut merged = ConfigFile::default();
+
+        if let Some(user_path) = user_config_path().filter(|path| path.is_file()) {
+            merged = merged.merge(Self::load(&user_path)?);
+        }
+
+        match project {
+            Some(path) => merged = merged.merge(Self::load(path)?),
+            None => {
+                let path = Path::new(CONFIG_FILE_NAME);
+                if path.is_file() {
+                    merged = merged.merge(Self::load(path)?);
+                }
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// `overlay` の値を優先してマージ（別名は両方を合わせる）
+    pub fn merge(self, overlay: ConfigFile) -> ConfigFile {
+        let mut aliases = self.authors.aliases;
+        aliases.extend(overlay.authors.aliases);
+
+        // branch と range は排他なので、どちらかを指定した側の設定をまとめて使う
+        let (branch, range) = if overlay.refs.branch.is_some() || overlay.refs.range.is_some() {
+            (overlay.refs.branch, overlay.refs.range)
+        } else {
+            (self.refs.branch, self.refs.range)
+        };
+
+        ConfigFile {
+            repository: RepositorySection {
+                path: overlay.repository.path.or(self.repository.path),
+                mirror_dir: overlay.repository.mirror_dir.or(self.repository.mirror_dir),
+                depth: overlay.repository.depth.or(self.repository.depth),
+                fetch_refs: overlay.repository.fetch_refs.or(self.repository.fetch_refs),
+            },
+            refs: RefsSection {
+                branch,
+                range,
+                first_parent: overlay.refs.first_parent.or(self.refs.first_parent),
+                order: overlay.refs.order.or(self.refs.order),
+            },
+            filters: FiltersSection {
+                since: overlay.filters.since.or(self.filters.since),
+                until: overlay.filters.until.or(self.filters.until),
+                limit: overlay.filters.limit.or(self.filters.limit),
+                exclude_paths: overlay.filters.exclude_paths.or(self.filters.exclude_paths),
+            },
+            authors: AuthorsSection { aliases },
+            output: OutputSection {
+                database: overlay.output.database.or(self.output.database),
+                format: overlay.output.format.or(self.output.format),
+            },
+            analysis: AnalysisSection {
+                incremental: overlay.analysis.incremental.or(self.analysis.incremental),
+                prune_orphans: overlay
+                    .analysis
+                    .prune_orphans
+                    .or(self.analysis.prune_orphans),
+                keep_going: overlay.analysis.keep_going.or(self.analysis.keep_going),
+                rollup_depth: overlay.analysis.rollup_depth.or(self.analysis.rollup_depth),
+                rollup_bucket: overlay
+                    .analysis
+                    .rollup_bucket
+                    .or(self.analysis.rollup_bucket),
+                timezone: overlay.analysis.timezone.or(self.analysis.timezone),
+                diff_algorithm: overlay
+                    .analysis
+                    .diff_algorithm
+                    .or(self.analysis.diff_algorithm),
+                diff_context_lines: overlay
+                    .analysis
+                    .diff_context_lines
+                    .or(self.analysis.diff_context_lines),
+                ignore_whitespace: overlay
+                    .analysis
+                    .ignore_whitespace
+                    .or(self.analysis.ignore_whitespace),
+                detect_renames: overlay
+                    .analysis
+                    .detect_renames
+                    .or(self.analysis.detect_renames),
+            },
+            thresholds: ThresholdsSection {
+                coupling_max_files: overlay
+                    .thresholds
+                    .coupling_max_files
+                    .or(self.thresholds.coupling_max_files),
+                coupling_min_co_changes: overlay
+                    .thresholds
+                    .coupling_min_co_changes
+                    .or(self.thresholds.coupling_min_co_changes),
+                coupling_min_confidence: overlay
+                    .thresholds
+                    .coupling_min_confidence
+                    .or(self.thresholds.coupling_min_confidence),
+                ownership_half_life_days: overlay
+                    .thresholds
+                    .ownership_half_life_days
+                    .or(self.thresholds.ownership_half_life_days),
+                ownership_inactive_days: overlay
+                    .thresholds
+                    .ownership_inactive_days
+                    .or(self.thresholds.ownership_inactive_days),
+                bulk_commit_files: overlay
+                    .thresholds
+                    .bulk_commit_files
+                    .or(self.thresholds.bulk_commit_files),
+                formatting_whitespace_share: overlay
+                    .thresholds
+                    .formatting_whitespace_share
+                    .or(self.thresholds.formatting_whitespace_share),
+            },
+        }
+    }
+
+    /// 値の整合性を検証（文字列で指定する値は解析できるかも確認する）
+    pub fn validate(&self) -> Result<()> {
+        if self.refs.branch.is_some() && self.refs.range.is_some() {
+            return Err(config_error(
+                "refs.branch and refs.range cannot be combined",
+            ));
+        }
+        if let Some(order) = &self.refs.order {
+            order.parse::<CommitOrder>()?;
+        }
+
+        let since = self
+            .filters
+            .since
+            .as_deref()
+            .map(|v| parse_date(v, false))
+            .transpose()?;
+        let until = self
+            .filters
+            .until
+            .as_deref()
+            .map(|v| parse_date(v, true))
+            .transpose()?;
+        if let (Some(since), Some(until)) = (since, until)
+            && since > until
+        {
+            return Err(config_error(
+                "filters.since must not be after filters.until",
+            ));
+        }
+        if self.filters.limit == Some(0) {
+            return Err(config_error("filters.limit must be at least 1"));
+        }
+        PathFilter::new(self.filters.exclude_paths.as_deref().unwrap_or_default())?;
+
+        AuthorAliases::new(&self.authors.aliases)?;
+
+        if let Some(format) = &self.output.format
+            && !OUTPUT_FORMATS.contains(&format.to_ascii_lowercase().as_str())
+        {
+            return Err(config_error(&format!(
+                "Unknown output.format: {} (expected table, csv or json)",
+                format
+            )));
+        }
+
+        if self.repository.depth == Some(0) {
+            return Err(config_error("repository.depth must be at least 1"));
+        }
+        if self.analysis.rollup_depth == Some(0) {
+            return Err(config_error("analysis.rollup_depth must be at least 1"));
+        }
+        if let Some(bucket) = &self.analysis.rollup_bucket {
+            bucket.parse::<TimeBucket>()?;
+        }
+        if let Some(timezone) = &self.analysis.timezone {
+            timezone.parse::<Timezone>()?;
+        }
+        if let Some(algorithm) = &self.analysis.diff_algorithm {
+            algorithm.parse::<DiffAlgorithm>()?;
+        }
+        if let Some(mode) = &self.analysis.ignore_whitespace {
+            mode.parse::<IgnoreWhitespace>()?;
+        }
+
+        if let Some(confidence) = self.thresholds.coupling_min_confidence
+            && !(0.0..=1.0).contains(&confidence)
+        {
+            return Err(config_error(
+                "thresholds.coupling_min_confidence must be between 0.0 and 1.0",
+            ));
+        }
+        if let Some(half_life) = self.thresholds.ownership_half_life_days
+            && half_life <= 0.0
+        {
+            return Err(config_error(
+                "thresholds.ownership_half_life_days must be positive",
+            ));
+        }
+        if self
+            .thresholds
+            .ownership_inactive_days
+            .is_some_and(|days| days < 0)
+        {
+            return Err(config_error(
+                "thresholds.ownership_inactive_days must not be negative",
+            ));
+        }
+        if self.thresholds.bulk_commit_files == Some(0) {
+            return Err(config_error(
+                "thresholds.bulk_commit_files must be at least 1",
+            ));
+        }
+        if let Some(share) = self.thresholds.formatting_whitespace_share
+            && !(0.0..=1.0).contains(&share)
+        {
+            return Err(config_error(
+                "thresholds.formatting_whitespace_share must be between 0.0 and 1.0",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 設定ファイルの値から解析設定を作成（未指定の値は組み込みの既定値）
+    pub fn to_config(&self) -> Result<Config> {
+        self.validate()?;
+
+        let repo = self.repository.path.as_deref().unwrap_or(".");
+        let output_db = self
+            .output
+            .database
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("git-history.db"));
+
+        let default_rollups = RollupOptions::default();
+        let default_diff = DiffStatOptions::default();
+        let default_commit_kinds = CommitKindOptions::default();
+        let config = Config::new(PathBuf::from(repo), output_db)
+            .with_remote(remote::is_remote_url(repo).then(|| repo.to_string()))
+            .with_mirror(MirrorOptions {
+                cache_dir: self
+                    .repository
+                    .mirror_dir
+                    .clone()
+                    .unwrap_or_else(remote::default_cache_dir),
+                depth: self.repository.depth,
+                refspecs: self.repository.fetch_refs.clone().unwrap_or_default(),
+            })
+            .with_branch(self.refs.branch.clone())
+            .with_revision_range(self.refs.range.clone())
+            .with_first_parent(self.refs.first_parent.unwrap_or(false))
+            .with_order(self.refs.order.as_deref().unwrap_or("time").parse()?)
+            .with_date_range(
+                self.filters
+                    .since
+                    .as_deref()
+                    .map(|v| parse_date(v, false))
+                    .transpose()?,
+                self.filters
+                    .until
+                    .as_deref()
+                    .map(|v| parse_date(v, true))
+                    .transpose()?,
+            )
+            .with_limit(self.filters.limit)
+            .with_path_filter(PathFilter::new(
+                self.filters.exclude_paths.as_deref().unwrap_or_default(),
+            )?)
+            .with_author_aliases(AuthorAliases::new(&self.authors.aliases)?)
+            .with_incremental(self.analysis.incremental.unwrap_or(false))
+            .with_prune_orphans(self.analysis.prune_orphans.unwrap_or(false))
+            .with_keep_going(self.analysis.keep_going.unwrap_or(false))
+            .with_rollups(RollupOptions {
+                depth: self.analysis.rollup_depth.unwrap_or(default_rollups.depth),
+                bucket: match &self.analysis.rollup_bucket {
+                    Some(bucket) => bucket.parse()?,
+                    None => default_rollups.bucket,
+                },
+            })
+            .with_timezone(match &self.analysis.timezone {
+                Some(timezone) => timezone.parse()?,
+                None => Timezone::Utc,
+            })
+            .with_diff_options(DiffStatOptions {
+                algorithm: match &self.analysis.diff_algorithm {
+                    Some(algorithm) => algorithm.parse()?,
+                    None => DiffAlgorithm::default(),
+                },
+                context_lines: self
+                    .analysis
+                    .diff_context_lines
+                    .unwrap_or(default_diff.context_lines),
+                ignore_whitespace: self
+                    .analysis
+                    .ignore_whitespace
+                    .as_deref()
+                    .map(str::parse)
+                    .transpose()?,
+                detect_renames: self
+                    .analysis
+                    .detect_renames
+                    .unwrap_or(default_diff.detect_renames),
+            })
+            .with_commit_kinds(CommitKindOptions {
+                bulk_files: self
+                    .thresholds
+                    .bulk_commit_files
+                    .unwrap_or(default_commit_kinds.bulk_files),
+                whitespace_share: self
+                    .thresholds
+                    .formatting_whitespace_share
+                    .unwrap_or(default_commit_kinds.whitespace_share),
+            });
+
+        Ok(config)
+    }
+}
+
+/// ユーザー設定のパス（`$XDG_CONFIG_HOME` または `~/.config` 配下）
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("git-history").join(CONFIG_FILE_NAME))
+}
+
+fn config_error(message: &str) -> GitHistoryError {
+    GitHistoryError::ConfigError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ConfigFile {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn merge_prefers_the_overlay_and_combines_aliases() {
+        let user = parse(
+            r#"
+            [refs]
+            range = "v1..HEAD"
+
+            [authors.aliases]
+            "ana@old.example" = "Ana <ana@example.com>"
+
+            [analysis]
+            timezone = "+09:00"
+            diff_context_lines = 5
+            "#,
+        );
+        let project = parse(
+            r#"
+            [refs]
+            branch = "main"
+
+            [authors.aliases]
+            "bo@old.example" = "Bo <bo@example.com>"
+
+            [analysis]
+            timezone = "utc"
+            "#,
+        );
+
+        let merged = user.merge(project);
+
+        // branch と range は片方の設定からまとめて取る
+        assert_eq!(merged.refs.branch.as_deref(), Some("main"));
+        assert_eq!(merged.refs.range, None);
+        assert_eq!(merged.analysis.timezone.as_deref(), Some("utc"));
+        assert_eq!(merged.analysis.diff_context_lines, Some(5));
+        assert_eq!(merged.authors.aliases.len(), 2);
+        merged.validate().unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_values() {
+        for content in [
+            "[refs]\nbranch = \"main\"\nrange = \"v1..v2\"",
+            "[refs]\norder = \"random\"",
+            "[filters]\nsince = \"2024-02-01\"\nuntil = \"2024-01-01\"",
+            "[filters]\nlimit = 0",
+            "[output]\nformat = \"xml\"",
+            "[analysis]\ntimezone = \"+25:00\"",
+            "[analysis]\ndiff_algorithm = \"histogram\"",
+            "[thresholds]\ncoupling_min_confidence = 1.5",
+            "[thresholds]\nformatting_whitespace_share = -0.1",
+        ] {
+            let error = parse(content).validate().unwrap_err();
+            assert!(
+                matches!(error, GitHistoryError::ConfigError(_)),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<ConfigFile>("[analysis]\nincremantal = true").is_err());
+    }
+
+    #[test]
+    fn settings_are_applied_to_the_analysis_config() {
+        let config = parse(
+            r#"
+            [analysis]
+            ignore_whitespace = "all"
+            detect_renames = true
+
+            [thresholds]
+            bulk_commit_files = 20
+            "#,
+        )
+        .to_config()
+        .unwrap();
+
+        assert_eq!(config.diff.ignore_whitespace, Some(IgnoreWhitespace::All));
+        assert!(config.diff.detect_renames);
+        assert_eq!(config.commit_kinds.bulk_files, 20);
+        assert_eq!(config.output_db, PathBuf::from("git-history.db"));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::database::CommitInfo;
+use crate::error::{GitHistoryError, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::HashMap;
+
+/// 解析から除外するファイルパスのパターン（glob）
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl PathFilter {
+    /// パターンから作成（`vendor/**`, `*.lock` など。`/` を含まないパターンは全階層に一致）
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern_str = if pattern.contains('/') {
+                pattern.clone()
+            } else {
+                format!("**/{}", pattern)
+            };
+            let glob = Glob::new(&pattern_str).map_err(|e| {
+                GitHistoryError::ConfigError(format!(
+                    "Invalid exclude pattern '{}': {}",
+                    pattern, e
+                ))
+            })?;
+            builder.add(glob);
+        }
+
+        let set = builder.build().map_err(|e| {
+            GitHistoryError::ConfigError(format!("Invalid exclude patterns: {}", e))
+        })?;
+
+        Ok(Self {
+            patterns: patterns.to_vec(),
+            set,
+        })
+    }
+
+    /// 除外対象のパスか
+    pub fn is_excluded(&self, path: &str) -> bool {
+        !self.patterns.is_empty() && self.set.is_match(path)
+    }
+
+    /// 設定されているパターン
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+}
+
+/// 作成者の別名（メールアドレスまたは名前 → 正規の名前とメールアドレス）
+#[derive(Debug, Clone, Default)]
+pub struct AuthorAliases {
+    aliases: HashMap<String, (String, String)>,
+}
+
+impl AuthorAliases {
+    /// `別名 = "Name <email>"` の組から作成（別名は大文字小文字を区別しない）
+    pub fn new<'a>(entries: impl IntoIterator<Item = (&'a String, &'a String)>) -> Result<Self> {
+        let mut aliases = HashMap::new();
+        for (alias, canonical) in entries {
+            let identity = parse_identity(canonical).ok_or_else(|| {
+                GitHistoryError::ConfigError(format!(
+                    "Invalid author alias for '{}': '{}' (expected \"Name <email>\")",
+                    alias, canonical
+                ))
+            })?;
+            aliases.insert(alias.trim().to_lowercase(), identity);
+        }
+        Ok(Self { aliases })
+    }
+
+    /// コミットの作成者を正規の名前とメールアドレスに置き換える（メールアドレスを優先して照合）
+    pub fn apply(&self, commit: &mut CommitInfo) {
+        if self.aliases.is_empty() {
+            return;
+        }
+
+        let identity = self
+            .aliases
+            .get(&commit.author_email.to_lowercase())
+            .or_else(|| self.aliases.get(&commit.author_name.to_lowercase()));
+        if let Some((name, email)) = identity {
+            commit.author_name = name.clone();
+            commit.author_email = email.clone();
+        }
+    }
+
+    /// 別名が1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+}
+
+/// `Name <email>` を (名前, メールアドレス) に分解
+fn parse_identity(value: &str) -> Option<(String, String)> {
+    let (name, rest) = value.split_once('<')?;
+    let email = rest.strip_suffix('>')?.trim();
+    let name = name.trim();
+    if name.is_empty() || email.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), email.to_string()))
+}
@@ -2,5 +2,10 @@ pub mod models;
 pub mod repository;
 pub mod schema;
 
-pub use models::{ChangeType, CommitInfo, FileChange};
+pub use models::{
+    ActivityBucket, AnalysisCheckpoint, AnalysisErrorRecord, AnalysisRun, AuthorStats, ChangeType,
+    ChurnRecord, CommitActivity, CommitFile, CommitInfo, CommitKind, CommitSummary, FileChange,
+    FileChangeStats, FileCommit, FileCoupling, FileRevision, Hotspot, ReleaseInfo, ReleaseStats,
+    RunStatus, TreeSnapshot,
+};
 pub use repository::Database;
@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 /// コミット情報
 #[derive(Debug, Clone)]
 pub struct CommitInfo {
@@ -7,9 +9,15 @@ pub struct CommitInfo {
     /// 親コミットハッシュ（最初の親のみ）
     pub parent_hash: Option<String>,
 
-    /// コミットメッセージ
+    /// すべての親コミットハッシュ（マージコミットは2つ以上、トポロジカル順位の再計算に使用）
+    pub parent_hashes: Vec<String>,
+
+    /// コミットメッセージ（encoding ヘッダーに従ってUTF-8に変換したもの）
     pub message: String,
 
+    /// 元のメッセージのバイト列（UTF-8として正しく、変換で失われる情報がない場合はNone）
+    pub message_raw: Option<Vec<u8>>,
+
     /// 作成者名
     pub author_name: String,
 
@@ -18,6 +26,12 @@ pub struct CommitInfo {
 
     /// コミット日時 (Unix timestamp)
     pub commit_date: i64,
+
+    /// コミット時のタイムゾーン（UTCからのオフセット、分）
+    pub commit_tz_offset: i32,
+
+    /// トポロジカル順位（親ほど小さい、累積カラムの計算順序に使用）
+    pub topo_order: Option<i64>,
 }
 
 /// ファイル変更情報
@@ -26,15 +40,27 @@ pub struct FileChange {
     /// コミットハッシュ
     pub commit_hash: String,
 
-    /// ファイルパス
+    /// ファイルパス（UTF-8でないバイトは `\xNN`、バックスラッシュは `\\` で表す）
     pub file_path: String,
 
+    /// 元のパスのバイト列（`file_path` と同じ場合はNone）
+    pub file_path_raw: Option<Vec<u8>>,
+
+    /// リネーム前のパス（リネームの場合のみ）
+    pub old_path: Option<String>,
+
     /// 追加行数
     pub lines_added: i32,
 
     /// 削除行数
     pub lines_deleted: i32,
 
+    /// 空白の変更を無視した追加行数（`ignore_whitespace` を指定した解析のみ）
+    pub lines_added_ws: Option<i32>,
+
+    /// 空白の変更を無視した削除行数
+    pub lines_deleted_ws: Option<i32>,
+
     /// コミット後の総行数（オプション）
     pub total_lines: Option<i32>,
 
@@ -43,6 +69,500 @@ pub struct FileChange {
 
     /// 変更種別
     pub change_type: ChangeType,
+
+    /// 変更前のblob OID（追加の場合はNone）
+    pub old_oid: Option<String>,
+
+    /// 変更後のblob OID（削除の場合はNone）
+    pub new_oid: Option<String>,
+
+    /// 変更前のファイルモード（例: 0o100644, 0o100755, 0o120000）
+    pub old_mode: Option<i32>,
+
+    /// 変更後のファイルモード
+    pub new_mode: Option<i32>,
+}
+
+/// ファイル履歴の1件（コミットとそのコミットでの変更）
+#[derive(Debug, Clone)]
+pub struct FileRevision {
+    pub commit: CommitInfo,
+    pub change: FileChange,
+}
+
+/// 作成者ごとのファイル変更量（集計用）
+#[derive(Debug, Clone)]
+pub struct ChurnRecord {
+    /// コミットハッシュ
+    pub commit_hash: String,
+
+    /// ファイルパス
+    pub file_path: String,
+
+    /// 変更種別 (ADD/MODIFY/DELETE/...)
+    pub change_type: String,
+
+    /// リネーム前のパス（リネームの場合のみ）
+    pub old_path: Option<String>,
+
+    /// 作成者名
+    pub author_name: String,
+
+    /// 作成者メールアドレス
+    pub author_email: String,
+
+    /// コミット日時 (Unix timestamp)
+    pub commit_date: i64,
+
+    /// 追加行数 + 削除行数
+    pub lines_changed: i32,
+
+    /// 追加行数
+    pub lines_added: i32,
+
+    /// 削除行数
+    pub lines_deleted: i32,
+}
+
+/// ファイル間の同時変更（論理結合）
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCoupling {
+    /// ファイルA（辞書順で小さい方）
+    pub file_a: String,
+
+    /// ファイルB
+    pub file_b: String,
+
+    /// 同じコミットで変更された回数
+    pub co_changes: i32,
+
+    /// ファイルAが変更されたコミット数
+    pub changes_a: i32,
+
+    /// ファイルBが変更されたコミット数
+    pub changes_b: i32,
+
+    /// 対象コミット全体に占める同時変更の割合
+    pub support: f64,
+
+    /// Aが変更されたときにBも変更される確率
+    pub confidence_ab: f64,
+
+    /// Bが変更されたときにAも変更される確率
+    pub confidence_ba: f64,
+}
+
+/// ファイルごとの変更頻度
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChangeStats {
+    /// ファイルパス
+    pub file_path: String,
+
+    /// 元のパスのバイト列（`file_path` と同じ場合はNone）
+    #[serde(skip)]
+    pub file_path_raw: Option<Vec<u8>>,
+
+    /// 変更されたコミット数
+    pub change_count: i64,
+
+    /// 追加行数 + 削除行数の合計
+    pub lines_changed: i64,
+}
+
+/// 作成者ごとの活動量
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorStats {
+    pub author_name: String,
+    pub author_email: String,
+
+    /// コミット数
+    pub commits: i64,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+
+    /// 最初のコミット日時 (Unix timestamp)
+    pub first_commit_date: i64,
+
+    /// 最後のコミット日時 (Unix timestamp)
+    pub last_commit_date: i64,
+}
+
+/// 一覧表示用のコミット（変更したファイル数と行数を含む）
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitSummary {
+    pub commit_hash: String,
+    pub author_name: String,
+    pub author_email: String,
+
+    /// コミット日時 (Unix timestamp)
+    pub commit_date: i64,
+
+    /// コミットメッセージ
+    pub message: String,
+
+    /// 変更したファイル数
+    pub files_changed: i64,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+}
+
+/// 1ファイルを変更したコミット（行数はそのファイル分のみ）
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCommit {
+    pub commit_hash: String,
+    pub author_name: String,
+    pub author_email: String,
+
+    /// コミット日時 (Unix timestamp)
+    pub commit_date: i64,
+
+    /// コミットメッセージ
+    pub message: String,
+
+    /// 変更種別 (ADD/MODIFY/DELETE/...)
+    pub change_type: String,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+}
+
+/// 1コミットで変更されたファイル
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitFile {
+    pub file_path: String,
+
+    /// 変更種別 (ADD/MODIFY/DELETE/...)
+    pub change_type: String,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+}
+
+/// ホットスポット（変更頻度 × 複雑度）
+#[derive(Debug, Clone, Serialize)]
+pub struct Hotspot {
+    /// ファイルパス
+    pub file_path: String,
+
+    /// 言語名
+    pub language: String,
+
+    /// 変更されたコミット数
+    pub change_count: i64,
+
+    /// 追加行数 + 削除行数の合計
+    pub lines_changed: i64,
+
+    /// 総行数（解析時点のリビジョン）
+    pub lines: i64,
+
+    /// 空行・コメント行を除いた行数
+    pub code_lines: i64,
+
+    /// 最大インデント深さ
+    pub max_depth: i64,
+
+    /// 平均インデント深さ
+    pub mean_depth: f64,
+
+    /// 関数定義の数（推定）
+    pub functions: i64,
+
+    /// 複雑度スコア
+    pub complexity: f64,
+
+    /// ホットスポットスコア (0.0〜1.0)
+    pub score: f64,
+
+    /// 複雑度を計算したコミット
+    pub revision: String,
+}
+
+/// コミットごとの活動量（時系列集計用）
+#[derive(Debug, Clone)]
+pub struct CommitActivity {
+    /// 作成者メールアドレス
+    pub author_email: String,
+
+    /// コミット日時 (Unix timestamp)
+    pub commit_date: i64,
+
+    /// コミット時のタイムゾーン（UTCからのオフセット、分）
+    pub commit_tz_offset: i32,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+
+    /// 新規追加されたファイル数
+    pub new_files: i64,
+}
+
+/// 期間ごとの活動量
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityBucket {
+    /// 時間単位 (day/week/month)
+    pub bucket: String,
+
+    /// 期間の開始日 (YYYY-MM-DD)
+    pub bucket_start: String,
+
+    /// 集計に使ったタイムゾーン
+    pub timezone: String,
+
+    /// コミット数
+    pub commits: i64,
+
+    /// コミットした作成者数
+    pub active_authors: i64,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+
+    /// 新規追加されたファイル数
+    pub new_files: i64,
+}
+
+/// リビジョン時点のコードベース規模
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeSnapshot {
+    /// コミットハッシュ
+    pub commit_hash: String,
+
+    /// コミット日時 (Unix timestamp)
+    pub snapshot_date: i64,
+
+    /// ラベル（タグ名または日付）
+    pub label: String,
+
+    /// 集計軸 (total/language/directory)
+    pub dimension: String,
+
+    /// 集計キー（言語名・ディレクトリ、totalの場合は"*"）
+    pub key: String,
+
+    /// ファイル数
+    pub files: i64,
+
+    /// 総行数（バイナリファイルは0）
+    pub lines: i64,
+}
+
+/// タグ（リリース）情報
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseInfo {
+    /// タグ名
+    pub tag_name: String,
+
+    /// タグが指すコミットハッシュ
+    pub target_commit: String,
+
+    /// タグ作成者名（軽量タグの場合はNone）
+    pub tagger_name: Option<String>,
+
+    /// タグ作成者メールアドレス（軽量タグの場合はNone）
+    pub tagger_email: Option<String>,
+
+    /// タグ日時 (Unix timestamp、軽量タグはコミット日時)
+    pub tag_date: i64,
+
+    /// タグメッセージ（軽量タグの場合はNone）
+    pub message: Option<String>,
+
+    /// 注釈付きタグかどうか
+    pub annotated: bool,
+}
+
+/// 前のリリースからの変更量
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReleaseStats {
+    /// 含まれるコミット数
+    pub commits: i64,
+
+    /// 作成者数（解析済みコミットのみ）
+    pub authors: i64,
+
+    /// 変更されたファイル数（解析済みコミットのみ）
+    pub files_changed: i64,
+
+    /// 追加行数
+    pub lines_added: i64,
+
+    /// 削除行数
+    pub lines_deleted: i64,
+}
+
+/// 中断した解析を再開するためのチェックポイント
+#[derive(Debug, Clone)]
+pub struct AnalysisCheckpoint {
+    /// 解析対象（リポジトリと選択条件）を識別するキー
+    pub selection_key: String,
+
+    /// 解析対象の全コミット数
+    pub total_commits: i64,
+
+    /// 保存済みのコミット数（コミット一覧の先頭からの件数）
+    pub processed_commits: i64,
+
+    /// 最後に保存したコミットのハッシュ
+    pub last_commit_hash: String,
+
+    /// トポロジカル順位の基準値（再開時も同じ値を使う）
+    pub base_topo_order: i64,
+}
+
+/// `--keep-going` で記録した1コミットの解析失敗
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisErrorRecord {
+    /// 失敗したコミットのハッシュ
+    pub commit_hash: String,
+
+    /// 失敗したファイルのパス（特定できた場合）
+    pub file_path: Option<String>,
+
+    /// エラーの分類（`GIT`, `IO`, `ANALYSIS` など）
+    pub category: String,
+
+    /// エラーメッセージ
+    pub message: String,
+}
+
+/// `analyze` の1回の実行の記録（analysis_runs テーブル）
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisRun {
+    /// 実行ID（commits / file_changes の `run_id` から参照）
+    pub run_id: i64,
+
+    /// 開始日時 (Unix timestamp)
+    pub started_at: i64,
+
+    /// 終了日時 (Unix timestamp、実行中または異常終了した場合はNone)
+    pub finished_at: Option<i64>,
+
+    /// 実行結果
+    pub status: RunStatus,
+
+    /// git-history のバージョン
+    pub tool_version: String,
+
+    /// 解析したリポジトリのパス（クローンURLの場合はミラーのパス）
+    pub repo_path: String,
+
+    /// クローンURL
+    pub remote_url: Option<String>,
+
+    /// 解析対象の参照（`--range`, `--branch`、どちらもなければ `HEAD`）
+    pub refs: String,
+
+    /// 解析オプション（JSON）
+    pub options: String,
+
+    /// 解析対象の参照が指していたコミット（JSON配列）
+    pub head_oids: String,
+
+    /// 選択されたコミット数
+    pub selected_commits: Option<i64>,
+
+    /// 今回処理したコミット数
+    pub processed_commits: Option<i64>,
+
+    /// 中断した前回の実行から引き継いだコミット数
+    pub resumed_commits: Option<i64>,
+
+    /// 解析に失敗したコミット数（`--keep-going`）
+    pub failed_commits: Option<i64>,
+
+    /// 孤立としてマークしたコミット数
+    pub orphaned_commits: Option<i64>,
+
+    /// 失敗・中断した場合のエラーメッセージ
+    pub error: Option<String>,
+}
+
+/// 実行結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunStatus {
+    /// 実行中（プロセスが強制終了された場合もこのまま残る）
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl RunStatus {
+    /// 文字列に変換
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Running => "RUNNING",
+            RunStatus::Completed => "COMPLETED",
+            RunStatus::Cancelled => "CANCELLED",
+            RunStatus::Failed => "FAILED",
+        }
+    }
+
+    /// 文字列から変換（不明な値は実行中として扱う）
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "COMPLETED" => RunStatus::Completed,
+            "CANCELLED" => RunStatus::Cancelled,
+            "FAILED" => RunStatus::Failed,
+            _ => RunStatus::Running,
+        }
+    }
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// レポートから除外するコミットの種類（commit_kinds テーブル）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitKind {
+    /// 多数のファイルを変更した一括変更
+    Bulk,
+    /// 変更のほとんどが空白のみ（フォーマッタによる整形など）
+    Formatting,
+    /// 内容を変えないリネーム・移動のみ
+    RenameOnly,
+}
+
+impl CommitKind {
+    /// 文字列に変換
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommitKind::Bulk => "BULK",
+            CommitKind::Formatting => "FORMATTING",
+            CommitKind::RenameOnly => "RENAME_ONLY",
+        }
+    }
+}
+
+impl std::fmt::Display for CommitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 /// 変更種別
@@ -52,6 +572,10 @@ pub enum ChangeType {
     Modify,
     Delete,
     Rename,
+    /// 内容は同一でファイルモードのみ変更（chmod +x など）
+    ModeChange,
+    /// ファイル種別の変更（通常ファイル ⇔ シンボリックリンク など）
+    TypeChange,
 }
 
 impl ChangeType {
@@ -62,6 +586,20 @@ impl ChangeType {
             ChangeType::Modify => "MODIFY",
             ChangeType::Delete => "DELETE",
             ChangeType::Rename => "RENAME",
+            ChangeType::ModeChange => "MODE_CHANGE",
+            ChangeType::TypeChange => "TYPE_CHANGE",
+        }
+    }
+
+    /// 文字列から変換（不明な値はModifyとして扱う）
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "ADD" => ChangeType::Add,
+            "DELETE" => ChangeType::Delete,
+            "RENAME" => ChangeType::Rename,
+            "MODE_CHANGE" => ChangeType::ModeChange,
+            "TYPE_CHANGE" => ChangeType::TypeChange,
+            _ => ChangeType::Modify,
         }
     }
 
@@ -72,6 +610,7 @@ impl ChangeType {
             git2::Delta::Modified => ChangeType::Modify,
             git2::Delta::Deleted => ChangeType::Delete,
             git2::Delta::Renamed => ChangeType::Rename,
+            git2::Delta::Typechange => ChangeType::TypeChange,
             _ => ChangeType::Modify, // その他はModifyとして扱う
         }
     }
@@ -1,18 +1,62 @@
-use crate::database::models::{CommitInfo, FileChange};
-use crate::error::Result;
-use duckdb::{params, Connection};
+use crate::analysis::rollups::TimeBucket;
+use crate::analysis::timeline::Timezone;
+use crate::database::models::{
+    ActivityBucket, AnalysisCheckpoint, AnalysisErrorRecord, AnalysisRun, AuthorStats, ChangeType,
+    ChurnRecord, CommitActivity, CommitFile, CommitInfo, CommitKind, CommitSummary, FileChange,
+    FileChangeStats, FileCommit, FileCoupling, FileRevision, Hotspot, ReleaseInfo, ReleaseStats,
+    RunStatus, TreeSnapshot,
+};
+use crate::error::{GitHistoryError, Result};
+use duckdb::{Connection, params};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::Path;
 
 /// データベース管理
 pub struct Database {
     conn: Connection,
+    /// ランキング系のレポートに commit_kinds で分類したコミットを含める
+    include_flagged: bool,
 }
 
 impl Database {
     /// データベースを開く（または作成）
     pub fn new(path: &Path) -> Result<Self> {
         let conn = Connection::open(path)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            include_flagged: false,
+        })
+    }
+
+    /// 一括変更・整形・リネームのみのコミット（commit_kinds）をレポートに含めるかを設定
+    pub fn with_flagged_commits(mut self, include_flagged: bool) -> Self {
+        self.include_flagged = include_flagged;
+        self
+    }
+
+    /// ランキング系のレポートが読むビュー（コミット, ファイル変更）
+    fn report_views(&self) -> (&'static str, &'static str) {
+        if self.include_flagged {
+            ("active_commits", "active_file_changes")
+        } else {
+            ("report_commits", "report_file_changes")
+        }
+    }
+
+    /// 既存のデータベースを開く（レポート用、存在しない場合はエラー）
+    ///
+    /// 古いスキーマのDBでもレポート用テーブルを使えるようにテーブル作成も行う。
+    pub fn open_existing(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(GitHistoryError::ConfigError(format!(
+                "Database not found: {} (run `git-history analyze` first)",
+                path.display()
+            )));
+        }
+        let database = Self::new(path)?;
+        database.create_tables()?;
+        Ok(database)
     }
 
     /// テーブルを作成
@@ -27,24 +71,881 @@ impl Database {
         }
 
         let tx = self.conn.transaction()?;
+        insert_commit_rows(&tx, commits, None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// ファイル変更情報をバッチ挿入
+    pub fn insert_file_changes(&mut self, changes: &[FileChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        insert_file_change_rows(&tx, changes, None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// コミットバッチとチェックポイントを1つのトランザクションで保存
+    ///
+    /// 途中で中断されても、チェックポイントは保存済みのコミットと常に一致する。
+    /// 解析に成功したコミットの過去の失敗記録は削除し、今回失敗したコミットの記録を追加する。
+    /// 挿入した行には実行ID（`run_id`）を記録する。
+    pub fn insert_commit_batch(
+        &mut self,
+        run_id: i64,
+        commits: &[CommitInfo],
+        changes: &[FileChange],
+        errors: &[AnalysisErrorRecord],
+        checkpoint: &AnalysisCheckpoint,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        insert_commit_rows(&tx, commits, Some(run_id))?;
+        insert_file_change_rows(&tx, changes, Some(run_id))?;
+        {
+            let mut clear = tx.prepare("DELETE FROM analysis_errors WHERE commit_hash = ?")?;
+            for commit_hash in commits
+                .iter()
+                .map(|c| &c.commit_hash)
+                .chain(errors.iter().map(|e| &e.commit_hash))
+            {
+                clear.execute(params![commit_hash])?;
+            }
+
+            let mut insert = tx.prepare(
+                r#"
+                INSERT INTO analysis_errors (commit_hash, file_path, category, message)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )?;
+            for error in errors {
+                insert.execute(params![
+                    &error.commit_hash,
+                    &error.file_path,
+                    &error.category,
+                    &error.message,
+                ])?;
+            }
+        }
+        tx.execute(
+            r#"
+            INSERT OR REPLACE INTO analysis_checkpoints
+            (selection_key, total_commits, processed_commits, last_commit_hash, base_topo_order,
+             updated_at)
+            VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+            params![
+                &checkpoint.selection_key,
+                checkpoint.total_commits,
+                checkpoint.processed_commits,
+                &checkpoint.last_commit_hash,
+                checkpoint.base_topo_order,
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 実行の開始を記録し、採番した実行IDを返す（`run.run_id` は無視する）
+    pub fn start_analysis_run(&mut self, run: &AnalysisRun) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+
+        let run_id: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(run_id), 0) + 1 FROM analysis_runs",
+            [],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            r#"
+            INSERT INTO analysis_runs
+            (run_id, started_at, status, tool_version, repo_path, remote_url, refs, options,
+             head_oids)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                run_id,
+                run.started_at,
+                run.status.as_str(),
+                &run.tool_version,
+                &run.repo_path,
+                &run.remote_url,
+                &run.refs,
+                &run.options,
+                &run.head_oids,
+            ],
+        )?;
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// 実行の終了（結果・件数・エラー）を記録
+    pub fn finish_analysis_run(&self, run: &AnalysisRun) -> Result<()> {
+        self.conn.execute(
+            r#"
+            UPDATE analysis_runs
+            SET finished_at = ?, status = ?, selected_commits = ?, processed_commits = ?,
+                resumed_commits = ?, failed_commits = ?, orphaned_commits = ?, error = ?
+            WHERE run_id = ?
+            "#,
+            params![
+                run.finished_at,
+                run.status.as_str(),
+                run.selected_commits,
+                run.processed_commits,
+                run.resumed_commits,
+                run.failed_commits,
+                run.orphaned_commits,
+                &run.error,
+                run.run_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 実行履歴を新しい順に取得
+    pub fn get_analysis_runs(&self) -> Result<Vec<AnalysisRun>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT run_id, started_at, finished_at, status, tool_version, repo_path, remote_url,
+                   refs, options, head_oids, selected_commits, processed_commits,
+                   resumed_commits, failed_commits, orphaned_commits, error
+            FROM analysis_runs
+            ORDER BY run_id DESC
+            "#,
+        )?;
+
+        let runs = stmt
+            .query_map([], |row| {
+                Ok(AnalysisRun {
+                    run_id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    finished_at: row.get(2)?,
+                    status: RunStatus::parse(&row.get::<_, String>(3)?),
+                    tool_version: row.get(4)?,
+                    repo_path: row.get(5)?,
+                    remote_url: row.get(6)?,
+                    refs: row.get(7)?,
+                    options: row.get(8)?,
+                    head_oids: row.get(9)?,
+                    selected_commits: row.get(10)?,
+                    processed_commits: row.get(11)?,
+                    resumed_commits: row.get(12)?,
+                    failed_commits: row.get(13)?,
+                    orphaned_commits: row.get(14)?,
+                    error: row.get(15)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(runs)
+    }
+
+    /// これまでの実行で解析した参照（ブランチ・リビジョン範囲・HEAD）を重複なく取得
+    pub fn get_analyzed_refs(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT refs FROM analysis_runs ORDER BY refs")?;
+        let refs = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(refs)
+    }
+
+    /// 解析対象のチェックポイントを取得
+    pub fn get_checkpoint(&self, selection_key: &str) -> Result<Option<AnalysisCheckpoint>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT selection_key, total_commits, processed_commits, last_commit_hash, base_topo_order
+            FROM analysis_checkpoints
+            WHERE selection_key = ?
+            "#,
+        )?;
+
+        let mut rows = stmt.query_map(params![selection_key], |row| {
+            Ok(AnalysisCheckpoint {
+                selection_key: row.get(0)?,
+                total_commits: row.get(1)?,
+                processed_commits: row.get(2)?,
+                last_commit_hash: row.get(3)?,
+                base_topo_order: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.next().transpose()?)
+    }
+
+    /// 解析が完了したチェックポイントを削除
+    pub fn clear_checkpoint(&self, selection_key: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM analysis_checkpoints WHERE selection_key = ?",
+            params![selection_key],
+        )?;
+        Ok(())
+    }
+
+    /// 記録済みのトポロジカル順位の最大値を取得（コミットがない場合は-1）
+    pub fn get_max_topo_order(&self) -> Result<i64> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT COALESCE(MAX(topo_order), -1) FROM commits")?;
+        let max: i64 = stmt.query_row([], |row| row.get(0))?;
+        Ok(max)
+    }
 
+    /// 記録済みの全コミットのトポロジカル順位（topo_order）を親子関係から再計算
+    ///
+    /// 実行ごとに付けた順位は、後の実行で祖先のコミットが追加される（`--since` を広げた、
+    /// `--limit` で途中までだった、分岐点の古いブランチを追加した）と親子の順序が逆転する。
+    /// 保存した parent_hashes から全体を並べ直し、順位が変わったコミット数を返す。
+    pub fn recompute_topo_order(&mut self) -> Result<usize> {
+        let commits = {
+            let mut stmt = self.conn.prepare(
+                r#"
+                SELECT commit_hash, parent_hashes, parent_hash, commit_date, topo_order
+                FROM commits
+                "#,
+            )?;
+            stmt.query_map([], |row| {
+                Ok(TopoNode {
+                    commit_hash: row.get(0)?,
+                    parents: parent_list(row.get(1)?, row.get(2)?),
+                    commit_date: row.get(3)?,
+                    topo_order: row.get(4)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let ranks = topological_ranks(&commits);
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "CREATE TEMP TABLE IF NOT EXISTS topo_ranks (commit_hash VARCHAR, topo_order BIGINT)",
+            [],
+        )?;
+        tx.execute("DELETE FROM topo_ranks", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO topo_ranks VALUES (?, ?)")?;
+            for (commit, rank) in commits.iter().zip(&ranks) {
+                if commit.topo_order != Some(*rank) {
+                    stmt.execute(params![&commit.commit_hash, rank])?;
+                }
+            }
+        }
+        let updated = tx.execute(
+            r#"
+            UPDATE commits SET topo_order = r.topo_order
+            FROM topo_ranks r
+            WHERE commits.commit_hash = r.commit_hash
+            "#,
+            [],
+        )?;
+
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// 累積コミット回数（commit_count）をトポロジカル順で再計算
+    ///
+    /// 処理順序や時計のずれたコミットに関係なく、親のコミットほど小さい値になる。
+    pub fn recompute_commit_counts(&mut self) -> Result<()> {
+        self.conn.execute(
+            r#"
+            UPDATE file_changes
+            SET commit_count = ranked.rn
+            FROM (
+                SELECT
+                    fc.commit_hash,
+                    fc.file_path,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY fc.file_path
+                        ORDER BY c.topo_order NULLS FIRST, c.commit_date, fc.commit_hash
+                    ) AS rn
+                FROM active_file_changes fc
+                JOIN active_commits c ON fc.commit_hash = c.commit_hash
+            ) AS ranked
+            WHERE file_changes.commit_hash = ranked.commit_hash
+              AND file_changes.file_path = ranked.file_path
+              AND file_changes.commit_count IS DISTINCT FROM ranked.rn
+            "#,
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// コミット数を取得
+    pub fn get_total_commits(&self) -> Result<usize> {
+        let mut stmt = self.conn.prepare("SELECT COUNT(*) FROM active_commits")?;
+        let count: i64 = stmt.query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// ユニークなファイル数を取得
+    pub fn get_total_files(&self) -> Result<usize> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT COUNT(DISTINCT file_path) FROM active_file_changes")?;
+        let count: i64 = stmt.query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// 作成者ごとのファイル変更量を取得（古い順）
+    pub fn get_churn_records(&self) -> Result<Vec<ChurnRecord>> {
+        self.get_churn_records_between(None, None)
+    }
+
+    /// 指定期間（Unix timestamp、両端を含む）のファイル変更量を取得（古い順）
+    pub fn get_churn_records_between(
+        &self,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<ChurnRecord>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT c.commit_hash, fc.file_path, fc.change_type, c.author_name, c.author_email,
+                   c.commit_date, fc.lines_added + fc.lines_deleted, fc.lines_added,
+                   fc.lines_deleted, fc.old_path
+            FROM {changes} fc
+            JOIN {commits} c ON fc.commit_hash = c.commit_hash
+            WHERE (CAST(? AS BIGINT) IS NULL OR c.commit_date >= ?)
+              AND (CAST(? AS BIGINT) IS NULL OR c.commit_date <= ?)
+            ORDER BY c.commit_date
+            "#
+        ))?;
+
+        let records = stmt
+            .query_map(params![since, since, until, until], |row| {
+                Ok(ChurnRecord {
+                    commit_hash: row.get(0)?,
+                    file_path: row.get(1)?,
+                    change_type: row.get(2)?,
+                    author_name: row.get(3)?,
+                    author_email: row.get(4)?,
+                    commit_date: row.get(5)?,
+                    lines_changed: row.get(6)?,
+                    lines_added: row.get(7)?,
+                    lines_deleted: row.get(8)?,
+                    old_path: row.get(9)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(records)
+    }
+
+    /// 作成者ごとの活動量をコミット数の多い順に取得
+    pub fn get_author_stats(&self) -> Result<Vec<AuthorStats>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            WITH churn AS (
+                SELECT commit_hash, SUM(lines_added) AS added, SUM(lines_deleted) AS deleted
+                FROM {changes}
+                GROUP BY commit_hash
+            )
+            SELECT arg_max(c.author_name, c.commit_date), c.author_email, COUNT(*),
+                   CAST(COALESCE(SUM(churn.added), 0) AS BIGINT),
+                   CAST(COALESCE(SUM(churn.deleted), 0) AS BIGINT),
+                   MIN(c.commit_date), MAX(c.commit_date)
+            FROM {commits} c
+            LEFT JOIN churn ON c.commit_hash = churn.commit_hash
+            GROUP BY c.author_email
+            ORDER BY COUNT(*) DESC, c.author_email
+            "#
+        ))?;
+
+        let authors = stmt
+            .query_map([], |row| {
+                Ok(AuthorStats {
+                    author_name: row.get(0)?,
+                    author_email: row.get(1)?,
+                    commits: row.get(2)?,
+                    lines_added: row.get(3)?,
+                    lines_deleted: row.get(4)?,
+                    first_commit_date: row.get(5)?,
+                    last_commit_date: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(authors)
+    }
+
+    /// コミットを新しい順に取得
+    ///
+    /// `search` はハッシュの先頭・メッセージ・作成者名・メールアドレスの部分一致（大文字小文字を区別しない）、
+    /// `author_email` は作成者の完全一致で絞り込む。
+    pub fn get_commit_summaries(
+        &self,
+        search: Option<&str>,
+        author_email: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            WITH churn AS (
+                SELECT commit_hash, COUNT(*) AS files,
+                       SUM(lines_added) AS added, SUM(lines_deleted) AS deleted
+                FROM {changes}
+                GROUP BY commit_hash
+            )
+            SELECT c.commit_hash, c.author_name, c.author_email, c.commit_date, c.message,
+                   CAST(COALESCE(churn.files, 0) AS BIGINT),
+                   CAST(COALESCE(churn.added, 0) AS BIGINT),
+                   CAST(COALESCE(churn.deleted, 0) AS BIGINT)
+            FROM {commits} c
+            LEFT JOIN churn ON c.commit_hash = churn.commit_hash
+            WHERE (CAST(? AS VARCHAR) IS NULL
+                   OR starts_with(c.commit_hash, lower(?))
+                   OR c.message ILIKE '%' || ? || '%'
+                   OR c.author_name ILIKE '%' || ? || '%'
+                   OR c.author_email ILIKE '%' || ? || '%')
+              AND (CAST(? AS VARCHAR) IS NULL OR c.author_email = ?)
+            ORDER BY c.commit_date DESC, c.commit_hash
+            LIMIT ?
+            "#
+        ))?;
+
+        let commits = stmt
+            .query_map(
+                params![
+                    search,
+                    search,
+                    search,
+                    search,
+                    search,
+                    author_email,
+                    author_email,
+                    limit as i64
+                ],
+                |row| {
+                    Ok(CommitSummary {
+                        commit_hash: row.get(0)?,
+                        author_name: row.get(1)?,
+                        author_email: row.get(2)?,
+                        commit_date: row.get(3)?,
+                        message: row.get(4)?,
+                        files_changed: row.get(5)?,
+                        lines_added: row.get(6)?,
+                        lines_deleted: row.get(7)?,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(commits)
+    }
+
+    /// ファイルを変更したコミットを新しい順に取得
+    pub fn get_file_commits(&self, file_path: &str) -> Result<Vec<FileCommit>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT c.commit_hash, c.author_name, c.author_email, c.commit_date, c.message,
+                   fc.change_type, fc.lines_added, fc.lines_deleted
+            FROM {changes} fc
+            JOIN {commits} c ON fc.commit_hash = c.commit_hash
+            WHERE fc.file_path = ?
+            ORDER BY c.commit_date DESC, c.commit_hash
+            "#
+        ))?;
+
+        let commits = stmt
+            .query_map(params![file_path], |row| {
+                Ok(FileCommit {
+                    commit_hash: row.get(0)?,
+                    author_name: row.get(1)?,
+                    author_email: row.get(2)?,
+                    commit_date: row.get(3)?,
+                    message: row.get(4)?,
+                    change_type: row.get(5)?,
+                    lines_added: row.get(6)?,
+                    lines_deleted: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(commits)
+    }
+
+    /// コミットで変更されたファイルをパス順に取得
+    pub fn get_commit_files(&self, commit_hash: &str) -> Result<Vec<CommitFile>> {
+        let (_, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT file_path, change_type, lines_added, lines_deleted
+            FROM {changes}
+            WHERE commit_hash = ?
+            ORDER BY file_path
+            "#
+        ))?;
+
+        let files = stmt
+            .query_map(params![commit_hash], |row| {
+                Ok(CommitFile {
+                    file_path: row.get(0)?,
+                    change_type: row.get(1)?,
+                    lines_added: row.get(2)?,
+                    lines_deleted: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(files)
+    }
+
+    /// ファイルの履歴を新しい順に取得（リネーム前のパスもたどる）
+    ///
+    /// リネームのコミットより前の履歴は、リネーム前のパスの変更として続けて返す。
+    pub fn file_history(&self, path: &str) -> Result<Vec<FileRevision>> {
+        let sql = format!(
+            "SELECT {COMMIT_COLUMNS}, {FILE_CHANGE_COLUMNS} \
+             FROM active_file_changes fc \
+             JOIN active_commits c ON fc.commit_hash = c.commit_hash \
+             WHERE fc.file_path = ? \
+             ORDER BY {NEWEST_FIRST}"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let mut history = Vec::new();
+        let mut path = path.to_string();
+        // リネームのコミットの並び順（これより前の変更だけを前のパスから拾う）
+        let mut before: Option<(i64, i64)> = None;
+        loop {
+            let revisions = stmt
+                .query_map(params![&path], |row| {
+                    Ok(FileRevision {
+                        commit: commit_from_row(row, 0)?,
+                        change: file_change_from_row(row, COMMIT_COLUMN_COUNT)?,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let mut renamed_from = None;
+            for revision in revisions {
+                let key = history_order(&revision.commit);
+                if before.is_some_and(|before| key >= before) {
+                    continue;
+                }
+                let old_path = match revision.change.change_type {
+                    ChangeType::Rename => revision.change.old_path.clone(),
+                    _ => None,
+                };
+                history.push(revision);
+                if let Some(old_path) = old_path {
+                    renamed_from = Some((old_path, key));
+                    break;
+                }
+            }
+
+            // 並び順は必ず古い方へ進むので、リネームが循環していても終了する
+            match renamed_from {
+                Some((old_path, key)) => {
+                    path = old_path;
+                    before = Some(key);
+                }
+                None => break,
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// 作成者の期間内（Unix timestamp、両端を含む。Noneは制限なし）のコミットを新しい順に取得
+    pub fn author_activity(
+        &self,
+        author_email: &str,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<CommitInfo>> {
+        let sql = format!(
+            "SELECT {COMMIT_COLUMNS} \
+             FROM active_commits c \
+             WHERE c.author_email = ? \
+               AND (CAST(? AS BIGINT) IS NULL OR c.commit_date >= ?) \
+               AND (CAST(? AS BIGINT) IS NULL OR c.commit_date <= ?) \
+             ORDER BY {NEWEST_FIRST}"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let commits = stmt
+            .query_map(params![author_email, since, since, until, until], |row| {
+                commit_from_row(row, 0)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(commits)
+    }
+
+    /// パス（ファイルまたはディレクトリ）以下を変更したコミットを新しい順に取得
+    ///
+    /// `src/db` は `src/db` と `src/db/...` に一致し、`src/dbx` には一致しない。空文字列は全コミット。
+    /// リネームは移動元のパスでも一致する。
+    pub fn commits_touching(&self, prefix: &str) -> Result<Vec<CommitInfo>> {
+        let prefix = prefix.trim_end_matches('/');
+        let sql = format!(
+            "SELECT {COMMIT_COLUMNS} \
+             FROM active_commits c \
+             WHERE c.commit_hash IN ( \
+                 SELECT commit_hash FROM active_file_changes \
+                 WHERE ? = '' OR file_path = ? OR starts_with(file_path, ? || '/') \
+                    OR old_path = ? OR starts_with(old_path, ? || '/') \
+             ) \
+             ORDER BY {NEWEST_FIRST}"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let commits = stmt
+            .query_map(params![prefix, prefix, prefix, prefix, prefix], |row| {
+                commit_from_row(row, 0)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(commits)
+    }
+
+    /// commit_kinds テーブルを再計算し、分類したコミット数を返す
+    ///
+    /// - `BULK`: `bulk_files` より多くのファイルを変更したコミット
+    /// - `FORMATTING`: 空白を無視した行数が記録されており、変更行のうち空白のみの変更が
+    ///   `whitespace_share` 以上を占めるコミット
+    /// - `RENAME_ONLY`: すべての変更が内容を変えないリネームのコミット
+    pub fn refresh_commit_kinds(
+        &mut self,
+        bulk_files: usize,
+        whitespace_share: f64,
+    ) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM commit_kinds", [])?;
+        tx.execute(
+            r#"
+            INSERT INTO commit_kinds (commit_hash, kind)
+            WITH per_commit AS (
+                SELECT commit_hash,
+                       COUNT(*) AS files,
+                       SUM(lines_added + lines_deleted) AS churn,
+                       COUNT(lines_added_ws) AS ws_files,
+                       SUM(lines_added_ws + lines_deleted_ws) AS ws_churn,
+                       COUNT(*) FILTER (
+                           WHERE change_type = 'RENAME' AND lines_added = 0 AND lines_deleted = 0
+                       ) AS pure_renames
+                FROM active_file_changes
+                GROUP BY commit_hash
+            )
+            SELECT commit_hash, CAST(? AS VARCHAR) FROM per_commit WHERE files > ?
+            UNION ALL
+            SELECT commit_hash, CAST(? AS VARCHAR) FROM per_commit
+            WHERE ws_files = files AND churn > 0 AND churn - ws_churn >= churn * ?
+            UNION ALL
+            SELECT commit_hash, CAST(? AS VARCHAR) FROM per_commit WHERE pure_renames = files
+            "#,
+            params![
+                CommitKind::Bulk.as_str(),
+                bulk_files as i64,
+                CommitKind::Formatting.as_str(),
+                whitespace_share,
+                CommitKind::RenameOnly.as_str(),
+            ],
+        )?;
+
+        let flagged: i64 = tx.query_row(
+            "SELECT COUNT(DISTINCT commit_hash) FROM commit_kinds",
+            [],
+            |row| row.get(0),
+        )?;
+
+        tx.commit()?;
+        Ok(flagged as usize)
+    }
+
+    /// file_coupling テーブルを再計算
+    ///
+    /// `max_files_per_commit` より多くのファイルを変更したコミットは集計から除外する。
+    pub fn refresh_file_coupling(
+        &mut self,
+        max_files_per_commit: usize,
+        min_co_changes: usize,
+    ) -> Result<()> {
+        let query = self.coupling_query();
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM file_coupling", [])?;
+        tx.execute(
+            &format!(
+                r#"
+            INSERT INTO file_coupling
+            (file_a, file_b, co_changes, changes_a, changes_b, support, confidence_ab, confidence_ba)
+            SELECT * FROM ({query})
+            "#
+            ),
+            params![max_files_per_commit as i64, min_co_changes.max(1) as i64],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 同時変更の集計結果を取得（同時変更回数の多い順）
+    pub fn get_file_couplings(&self, min_confidence: f64) -> Result<Vec<FileCoupling>> {
+        self.query_file_couplings("file_coupling", params![min_confidence])
+    }
+
+    /// file_coupling テーブルを使わずに同時変更をその場で集計
+    pub fn compute_file_couplings(
+        &self,
+        max_files_per_commit: usize,
+        min_co_changes: usize,
+        min_confidence: f64,
+    ) -> Result<Vec<FileCoupling>> {
+        self.query_file_couplings(
+            &format!("({})", self.coupling_query()),
+            params![
+                max_files_per_commit as i64,
+                min_co_changes.max(1) as i64,
+                min_confidence
+            ],
+        )
+    }
+
+    /// 同時変更を集計するクエリ（パラメータはコミットあたりの最大ファイル数と最小同時変更回数）
+    fn coupling_query(&self) -> String {
+        let (_, changes) = self.report_views();
+        format!(
+            r#"
+            WITH eligible AS (
+                SELECT commit_hash
+                FROM {changes}
+                GROUP BY commit_hash
+                HAVING COUNT(*) <= ?
+            ),
+            changes AS (
+                SELECT DISTINCT fc.commit_hash, fc.file_path
+                FROM {changes} fc
+                JOIN eligible e ON fc.commit_hash = e.commit_hash
+            ),
+            file_counts AS (
+                SELECT file_path, COUNT(*) AS n
+                FROM changes
+                GROUP BY file_path
+            ),
+            pairs AS (
+                SELECT a.file_path AS file_a, b.file_path AS file_b, COUNT(*) AS co_changes
+                FROM changes a
+                JOIN changes b ON a.commit_hash = b.commit_hash AND a.file_path < b.file_path
+                GROUP BY a.file_path, b.file_path
+                HAVING COUNT(*) >= ?
+            )
+            SELECT
+                p.file_a,
+                p.file_b,
+                p.co_changes,
+                ca.n AS changes_a,
+                cb.n AS changes_b,
+                p.co_changes / (SELECT COUNT(*) FROM eligible) AS support,
+                p.co_changes / ca.n AS confidence_ab,
+                p.co_changes / cb.n AS confidence_ba
+            FROM pairs p
+            JOIN file_counts ca ON p.file_a = ca.file_path
+            JOIN file_counts cb ON p.file_b = cb.file_path
+            "#
+        )
+    }
+
+    /// 同時変更の行を確信度で絞り込んで取得（最後のパラメータが最小確信度）
+    fn query_file_couplings(
+        &self,
+        source: &str,
+        params: &[&dyn duckdb::ToSql],
+    ) -> Result<Vec<FileCoupling>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT file_a, file_b, co_changes, changes_a, changes_b, support, confidence_ab, confidence_ba
+            FROM {source}
+            WHERE GREATEST(confidence_ab, confidence_ba) >= ?
+            ORDER BY co_changes DESC, GREATEST(confidence_ab, confidence_ba) DESC, file_a, file_b
+            "#
+        ))?;
+
+        let couplings = stmt
+            .query_map(params, |row| {
+                Ok(FileCoupling {
+                    file_a: row.get(0)?,
+                    file_b: row.get(1)?,
+                    co_changes: row.get(2)?,
+                    changes_a: row.get(3)?,
+                    changes_b: row.get(4)?,
+                    support: row.get(5)?,
+                    confidence_ab: row.get(6)?,
+                    confidence_ba: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(couplings)
+    }
+
+    /// ファイルごとの変更頻度を取得
+    pub fn get_file_change_stats(&self) -> Result<Vec<FileChangeStats>> {
+        let (_, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT file_path, ANY_VALUE(file_path_raw), COUNT(*), SUM(lines_added + lines_deleted)
+            FROM {changes}
+            GROUP BY file_path
+            "#
+        ))?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(FileChangeStats {
+                    file_path: row.get(0)?,
+                    file_path_raw: row.get(1)?,
+                    change_count: row.get(2)?,
+                    lines_changed: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// hotspots テーブルを置き換え
+    pub fn replace_hotspots(&mut self, hotspots: &[Hotspot]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM hotspots", [])?;
         {
             let mut stmt = tx.prepare(
                 r#"
-                INSERT OR IGNORE INTO commits
-                (commit_hash, parent_hash, message, author_name, author_email, commit_date)
-                VALUES (?, ?, ?, ?, ?, ?)
+                INSERT INTO hotspots
+                (file_path, language, change_count, lines_changed, lines, code_lines,
+                 max_depth, mean_depth, functions, complexity, score, revision)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
             )?;
 
-            for commit in commits {
+            for hotspot in hotspots {
                 stmt.execute(params![
-                    &commit.commit_hash,
-                    &commit.parent_hash,
-                    &commit.message,
-                    &commit.author_name,
-                    &commit.author_email,
-                    &commit.commit_date,
+                    &hotspot.file_path,
+                    &hotspot.language,
+                    &hotspot.change_count,
+                    &hotspot.lines_changed,
+                    &hotspot.lines,
+                    &hotspot.code_lines,
+                    &hotspot.max_depth,
+                    &hotspot.mean_depth,
+                    &hotspot.functions,
+                    &hotspot.complexity,
+                    &hotspot.score,
+                    &hotspot.revision,
                 ])?;
             }
         }
@@ -53,32 +954,231 @@ impl Database {
         Ok(())
     }
 
-    /// ファイル変更情報をバッチ挿入
-    pub fn insert_file_changes(&mut self, changes: &[FileChange]) -> Result<()> {
-        if changes.is_empty() {
+    /// ホットスポットをスコアの高い順に取得
+    pub fn get_hotspots(&self, limit: usize) -> Result<Vec<Hotspot>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT file_path, language, change_count, lines_changed, lines, code_lines,
+                   max_depth, mean_depth, functions, complexity, score, revision
+            FROM hotspots
+            ORDER BY score DESC, change_count DESC, file_path
+            LIMIT ?
+            "#,
+        )?;
+
+        let hotspots = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(Hotspot {
+                    file_path: row.get(0)?,
+                    language: row.get(1)?,
+                    change_count: row.get(2)?,
+                    lines_changed: row.get(3)?,
+                    lines: row.get(4)?,
+                    code_lines: row.get(5)?,
+                    max_depth: row.get(6)?,
+                    mean_depth: row.get(7)?,
+                    functions: row.get(8)?,
+                    complexity: row.get(9)?,
+                    score: row.get(10)?,
+                    revision: row.get(11)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(hotspots)
+    }
+
+    /// dir_rollups テーブルを再計算
+    ///
+    /// 深さ1〜`depth`の各ディレクトリについて、`timezone` での `bucket`（day/week/month）単位で集計する。
+    pub fn refresh_dir_rollups(
+        &mut self,
+        depth: usize,
+        bucket: TimeBucket,
+        timezone: Timezone,
+    ) -> Result<()> {
+        let (commits, changes) = self.report_views();
+        let offset_minutes = match timezone {
+            Timezone::Utc => "0".to_string(),
+            Timezone::Author => "c.commit_tz_offset".to_string(),
+            Timezone::Fixed(minutes) => minutes.to_string(),
+        };
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM dir_rollups", [])?;
+        {
+            let mut stmt = tx.prepare(&format!(
+                r#"
+                INSERT INTO dir_rollups
+                (dir_prefix, depth, bucket, bucket_start, commits, authors,
+                 lines_added, lines_deleted, files_touched)
+                SELECT
+                    CASE
+                        WHEN len(string_split(fc.file_path, '/')) = 1 THEN '.'
+                        ELSE array_to_string(
+                            list_slice(
+                                string_split(fc.file_path, '/'),
+                                1,
+                                LEAST(len(string_split(fc.file_path, '/')) - 1, CAST($1 AS BIGINT))
+                            ),
+                            '/'
+                        )
+                    END AS dir_prefix,
+                    CAST($1 AS INTEGER) AS depth,
+                    '{bucket}' AS bucket,
+                    CAST(date_trunc('{bucket}', epoch_ms((c.commit_date + {offset_minutes} * 60) * 1000)) AS DATE) AS bucket_start,
+                    COUNT(DISTINCT fc.commit_hash),
+                    COUNT(DISTINCT c.author_email),
+                    SUM(fc.lines_added),
+                    SUM(fc.lines_deleted),
+                    COUNT(DISTINCT fc.file_path)
+                FROM {changes} fc
+                JOIN {commits} c ON fc.commit_hash = c.commit_hash
+                GROUP BY 1, 2, 3, 4
+                "#,
+                bucket = bucket.as_str()
+            ))?;
+
+            for level in 1..=depth {
+                stmt.execute(params![level as i64])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// コミットごとの活動量を取得
+    pub fn get_commit_activity(&self) -> Result<Vec<CommitActivity>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT
+                c.author_email,
+                c.commit_date,
+                COALESCE(c.commit_tz_offset, 0),
+                COALESCE(SUM(fc.lines_added), 0),
+                COALESCE(SUM(fc.lines_deleted), 0),
+                COUNT(*) FILTER (WHERE fc.change_type = 'ADD')
+            FROM {commits} c
+            LEFT JOIN {changes} fc ON c.commit_hash = fc.commit_hash
+            GROUP BY c.commit_hash, c.author_email, c.commit_date, c.commit_tz_offset
+            "#
+        ))?;
+
+        let activity = stmt
+            .query_map([], |row| {
+                Ok(CommitActivity {
+                    author_email: row.get(0)?,
+                    commit_date: row.get(1)?,
+                    commit_tz_offset: row.get(2)?,
+                    lines_added: row.get(3)?,
+                    lines_deleted: row.get(4)?,
+                    new_files: row.get(5)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(activity)
+    }
+
+    /// activity_timeline テーブルを置き換え
+    pub fn replace_activity_timeline(&mut self, buckets: &[ActivityBucket]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM activity_timeline", [])?;
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT INTO activity_timeline
+                (bucket, bucket_start, timezone, commits, active_authors,
+                 lines_added, lines_deleted, new_files)
+                VALUES (?, CAST(? AS DATE), ?, ?, ?, ?, ?, ?)
+                "#,
+            )?;
+
+            for bucket in buckets {
+                stmt.execute(params![
+                    &bucket.bucket,
+                    &bucket.bucket_start,
+                    &bucket.timezone,
+                    &bucket.commits,
+                    &bucket.active_authors,
+                    &bucket.lines_added,
+                    &bucket.lines_deleted,
+                    &bucket.new_files,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 期間ごとの活動量を古い順に取得
+    pub fn get_activity_timeline(&self, bucket: TimeBucket) -> Result<Vec<ActivityBucket>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT bucket, CAST(bucket_start AS VARCHAR), timezone, commits, active_authors,
+                   lines_added, lines_deleted, new_files
+            FROM activity_timeline
+            WHERE bucket = ?
+            ORDER BY bucket_start
+            "#,
+        )?;
+
+        let buckets = stmt
+            .query_map(params![bucket.as_str()], |row| {
+                Ok(ActivityBucket {
+                    bucket: row.get(0)?,
+                    bucket_start: row.get(1)?,
+                    timezone: row.get(2)?,
+                    commits: row.get(3)?,
+                    active_authors: row.get(4)?,
+                    lines_added: row.get(5)?,
+                    lines_deleted: row.get(6)?,
+                    new_files: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(buckets)
+    }
+
+    /// スナップショットを保存（同じコミットの既存データは置き換え）
+    pub fn insert_tree_snapshots(&mut self, snapshots: &[TreeSnapshot]) -> Result<()> {
+        if snapshots.is_empty() {
             return Ok(());
         }
 
         let tx = self.conn.transaction()?;
 
         {
+            let mut delete_stmt = tx.prepare("DELETE FROM tree_snapshots WHERE commit_hash = ?")?;
+            let mut commit_hashes: Vec<&str> =
+                snapshots.iter().map(|s| s.commit_hash.as_str()).collect();
+            commit_hashes.dedup();
+            for commit_hash in commit_hashes {
+                delete_stmt.execute(params![commit_hash])?;
+            }
+
             let mut stmt = tx.prepare(
                 r#"
-                INSERT OR IGNORE INTO file_changes
-                (commit_hash, file_path, lines_added, lines_deleted, total_lines, commit_count, change_type)
+                INSERT INTO tree_snapshots
+                (commit_hash, snapshot_date, label, dimension, key, files, lines)
                 VALUES (?, ?, ?, ?, ?, ?, ?)
                 "#,
             )?;
 
-            for change in changes {
+            for snapshot in snapshots {
                 stmt.execute(params![
-                    &change.commit_hash,
-                    &change.file_path,
-                    &change.lines_added,
-                    &change.lines_deleted,
-                    &change.total_lines,
-                    &change.commit_count,
-                    change.change_type.as_str(),
+                    &snapshot.commit_hash,
+                    &snapshot.snapshot_date,
+                    &snapshot.label,
+                    &snapshot.dimension,
+                    &snapshot.key,
+                    &snapshot.files,
+                    &snapshot.lines,
                 ])?;
             }
         }
@@ -87,30 +1187,609 @@ impl Database {
         Ok(())
     }
 
-    /// ファイルのコミット回数を取得
-    pub fn get_commit_count(&self, file_path: &str) -> Result<i32> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT COUNT(*) FROM file_changes WHERE file_path = ?")?;
+    /// スナップショットを取得（日付の古い順、同日内は行数の多い順）
+    pub fn get_tree_snapshots(&self, dimension: &str) -> Result<Vec<TreeSnapshot>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT commit_hash, snapshot_date, label, dimension, key, files, lines
+            FROM tree_snapshots
+            WHERE dimension = ?
+            ORDER BY snapshot_date, lines DESC, key
+            "#,
+        )?;
 
-        let count: i32 = stmt.query_row(params![file_path], |row| row.get(0))?;
+        let snapshots = stmt
+            .query_map(params![dimension], |row| {
+                Ok(TreeSnapshot {
+                    commit_hash: row.get(0)?,
+                    snapshot_date: row.get(1)?,
+                    label: row.get(2)?,
+                    dimension: row.get(3)?,
+                    key: row.get(4)?,
+                    files: row.get(5)?,
+                    lines: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        Ok(count)
+        Ok(snapshots)
     }
 
-    /// コミット数を取得
-    pub fn get_total_commits(&self) -> Result<usize> {
-        let mut stmt = self.conn.prepare("SELECT COUNT(*) FROM commits")?;
-        let count: i64 = stmt.query_row([], |row| row.get(0))?;
-        Ok(count as usize)
+    /// releases / release_commits テーブルを置き換え
+    ///
+    /// `releases` は (リリース, 直前のリリースのタグ名, 含まれるコミット) の組。
+    pub fn replace_releases(
+        &mut self,
+        releases: &[(ReleaseInfo, Option<String>, Vec<String>)],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM releases", [])?;
+        tx.execute("DELETE FROM release_commits", [])?;
+        {
+            let mut release_stmt = tx.prepare(
+                r#"
+                INSERT INTO releases
+                (tag_name, target_commit, tagger_name, tagger_email, tag_date, message,
+                 annotated, previous_tag)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )?;
+            let mut commit_stmt =
+                tx.prepare("INSERT INTO release_commits (tag_name, commit_hash) VALUES (?, ?)")?;
+
+            for (release, previous_tag, commit_hashes) in releases {
+                release_stmt.execute(params![
+                    &release.tag_name,
+                    &release.target_commit,
+                    &release.tagger_name,
+                    &release.tagger_email,
+                    &release.tag_date,
+                    &release.message,
+                    &release.annotated,
+                    previous_tag,
+                ])?;
+
+                for commit_hash in commit_hashes {
+                    commit_stmt.execute(params![&release.tag_name, commit_hash])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
     }
 
-    /// ユニークなファイル数を取得
-    pub fn get_total_files(&self) -> Result<usize> {
-        let mut stmt = self
+    /// リリースごとの変更量を取得
+    pub fn get_release_stats(&self) -> Result<HashMap<String, ReleaseStats>> {
+        let (commits, changes) = self.report_views();
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT
+                rc.tag_name,
+                COUNT(DISTINCT c.commit_hash),
+                COUNT(DISTINCT c.author_email),
+                COUNT(DISTINCT fc.file_path),
+                COALESCE(SUM(fc.lines_added), 0),
+                COALESCE(SUM(fc.lines_deleted), 0)
+            FROM release_commits rc
+            LEFT JOIN {commits} c ON rc.commit_hash = c.commit_hash
+            LEFT JOIN {changes} fc ON c.commit_hash = fc.commit_hash
+            GROUP BY rc.tag_name
+            "#
+        ))?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    ReleaseStats {
+                        commits: row.get(1)?,
+                        authors: row.get(2)?,
+                        files_changed: row.get(3)?,
+                        lines_added: row.get(4)?,
+                        lines_deleted: row.get(5)?,
+                    },
+                ))
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// 記録済みの全コミットハッシュを取得（孤立したコミットを含む）
+    pub fn get_commit_hashes(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT commit_hash FROM commits")?;
+        let hashes = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(hashes)
+    }
+
+    /// 到達不能なコミットを孤立としてマーク（検出日時を記録）
+    ///
+    /// `unreachable` に含まれないコミットは孤立の記録を解除する。
+    /// 新たに孤立としてマークされたコミット数を返す。
+    pub fn update_orphaned_commits(&mut self, unreachable: &[String]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute(
+            "CREATE TEMP TABLE IF NOT EXISTS unreachable_commits (commit_hash VARCHAR)",
+            [],
+        )?;
+        tx.execute("DELETE FROM unreachable_commits", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT INTO unreachable_commits VALUES (?)")?;
+            for commit_hash in unreachable {
+                stmt.execute(params![commit_hash])?;
+            }
+        }
+
+        let newly_orphaned = tx.execute(
+            r#"
+            UPDATE commits SET orphaned_at = CURRENT_TIMESTAMP
+            WHERE orphaned_at IS NULL
+              AND commit_hash IN (SELECT commit_hash FROM unreachable_commits)
+            "#,
+            [],
+        )?;
+        tx.execute(
+            r#"
+            UPDATE commits SET orphaned_at = NULL
+            WHERE orphaned_at IS NOT NULL
+              AND commit_hash NOT IN (SELECT commit_hash FROM unreachable_commits)
+            "#,
+            [],
+        )?;
+
+        tx.commit()?;
+        Ok(newly_orphaned)
+    }
+
+    /// 孤立したコミットとそれを参照する行を削除し、削除したコミット数を返す
+    pub fn prune_orphaned_commits(&mut self) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+
+        for table in [
+            "file_changes",
+            "commit_kinds",
+            "analysis_errors",
+            "release_commits",
+        ] {
+            tx.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE commit_hash IN \
+                     (SELECT commit_hash FROM commits WHERE orphaned_at IS NOT NULL)"
+                ),
+                [],
+            )?;
+        }
+        let pruned = tx.execute("DELETE FROM commits WHERE orphaned_at IS NOT NULL", [])?;
+
+        tx.commit()?;
+        Ok(pruned)
+    }
+}
+
+/// `CommitInfo` として読み込むカラム（`c` は commits の別名）
+const COMMIT_COLUMNS: &str = "c.commit_hash, c.parent_hash, c.message, c.message_raw, \
+     c.author_name, c.author_email, c.commit_date, c.commit_tz_offset, c.topo_order, \
+     c.parent_hashes";
+
+/// `COMMIT_COLUMNS` のカラム数
+const COMMIT_COLUMN_COUNT: usize = 10;
+
+/// `FileChange` として読み込むカラム（`fc` は file_changes の別名）
+const FILE_CHANGE_COLUMNS: &str = "fc.commit_hash, fc.file_path, fc.file_path_raw, fc.old_path, \
+     fc.lines_added, fc.lines_deleted, fc.total_lines, fc.commit_count, fc.change_type, \
+     fc.old_oid, fc.new_oid, fc.old_mode, fc.new_mode, fc.lines_added_ws, fc.lines_deleted_ws";
+
+/// 新しい順（同じ日時はトポロジカル順位の大きい方を先に）
+const NEWEST_FIRST: &str = "c.commit_date DESC, c.topo_order DESC NULLS LAST, c.commit_hash";
+
+/// `COMMIT_COLUMNS` の行を `offset` 番目のカラムから読み込む
+fn commit_from_row(row: &duckdb::Row, offset: usize) -> duckdb::Result<CommitInfo> {
+    Ok(CommitInfo {
+        commit_hash: row.get(offset)?,
+        parent_hash: row.get(offset + 1)?,
+        message: row.get(offset + 2)?,
+        message_raw: row.get(offset + 3)?,
+        author_name: row.get(offset + 4)?,
+        author_email: row.get(offset + 5)?,
+        commit_date: row.get(offset + 6)?,
+        commit_tz_offset: row.get::<_, Option<i32>>(offset + 7)?.unwrap_or(0),
+        topo_order: row.get(offset + 8)?,
+        parent_hashes: parent_list(row.get(offset + 9)?, row.get(offset + 1)?),
+    })
+}
+
+/// 空白区切りの parent_hashes を分解（カラム追加前の行は最初の親のみ）
+fn parent_list(parent_hashes: Option<String>, parent_hash: Option<String>) -> Vec<String> {
+    match parent_hashes {
+        Some(hashes) => hashes.split_whitespace().map(str::to_string).collect(),
+        None => parent_hash.into_iter().collect(),
+    }
+}
+
+/// `FILE_CHANGE_COLUMNS` の行を `offset` 番目のカラムから読み込む
+fn file_change_from_row(row: &duckdb::Row, offset: usize) -> duckdb::Result<FileChange> {
+    Ok(FileChange {
+        commit_hash: row.get(offset)?,
+        file_path: row.get(offset + 1)?,
+        file_path_raw: row.get(offset + 2)?,
+        old_path: row.get(offset + 3)?,
+        lines_added: row.get(offset + 4)?,
+        lines_deleted: row.get(offset + 5)?,
+        total_lines: row.get(offset + 6)?,
+        commit_count: row.get(offset + 7)?,
+        change_type: ChangeType::parse(&row.get::<_, String>(offset + 8)?),
+        old_oid: row.get(offset + 9)?,
+        new_oid: row.get(offset + 10)?,
+        old_mode: row.get(offset + 11)?,
+        new_mode: row.get(offset + 12)?,
+        lines_added_ws: row.get(offset + 13)?,
+        lines_deleted_ws: row.get(offset + 14)?,
+    })
+}
+
+/// `NEWEST_FIRST` と同じ並び順のキー（大きいほど新しい）
+fn history_order(commit: &CommitInfo) -> (i64, i64) {
+    (commit.commit_date, commit.topo_order.unwrap_or(i64::MIN))
+}
+
+/// トポロジカル順位を再計算するコミット
+struct TopoNode {
+    commit_hash: String,
+    parents: Vec<String>,
+    commit_date: i64,
+    topo_order: Option<i64>,
+}
+
+/// 親が必ず子より小さくなる順位を `commits` と同じ並びで返す
+///
+/// 記録されていない親は無視する。同時に並べられるコミットは日時の古い順、
+/// 同じ日時なら以前の順位の小さい順に並べる。
+fn topological_ranks(commits: &[TopoNode]) -> Vec<i64> {
+    let index: HashMap<&str, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| (commit.commit_hash.as_str(), i))
+        .collect();
+
+    let mut pending_parents = vec![0usize; commits.len()];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); commits.len()];
+    for (i, commit) in commits.iter().enumerate() {
+        let parents: HashSet<usize> = commit
+            .parents
+            .iter()
+            .filter_map(|parent| index.get(parent.as_str()).copied())
+            .collect();
+        pending_parents[i] = parents.len();
+        for parent in parents {
+            children[parent].push(i);
+        }
+    }
+
+    let key = |i: usize| {
+        let commit = &commits[i];
+        Reverse((
+            commit.commit_date,
+            commit.topo_order.unwrap_or(i64::MAX),
+            commit.commit_hash.as_str(),
+            i,
+        ))
+    };
+    let mut ready: BinaryHeap<_> = (0..commits.len())
+        .filter(|&i| pending_parents[i] == 0)
+        .map(key)
+        .collect();
+
+    let mut ranks = vec![0; commits.len()];
+    let mut next = 0;
+    while let Some(Reverse((_, _, _, i))) = ready.pop() {
+        ranks[i] = next;
+        next += 1;
+        for &child in &children[i] {
+            pending_parents[child] -= 1;
+            if pending_parents[child] == 0 {
+                ready.push(key(child));
+            }
+        }
+    }
+
+    ranks
+}
+
+/// コミット行を挿入（呼び出し側のトランザクション内で実行）
+fn insert_commit_rows(
+    conn: &Connection,
+    commits: &[CommitInfo],
+    run_id: Option<i64>,
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT OR IGNORE INTO commits
+        (commit_hash, parent_hash, message, author_name, author_email, commit_date,
+         commit_tz_offset, topo_order, message_raw, run_id, parent_hashes)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )?;
+
+    for commit in commits {
+        stmt.execute(params![
+            &commit.commit_hash,
+            &commit.parent_hash,
+            &commit.message,
+            &commit.author_name,
+            &commit.author_email,
+            &commit.commit_date,
+            &commit.commit_tz_offset,
+            &commit.topo_order,
+            &commit.message_raw,
+            run_id,
+            commit.parent_hashes.join(" "),
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// ファイル変更行を挿入（呼び出し側のトランザクション内で実行）
+fn insert_file_change_rows(
+    conn: &Connection,
+    changes: &[FileChange],
+    run_id: Option<i64>,
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        r#"
+        INSERT OR IGNORE INTO file_changes
+        (commit_hash, file_path, lines_added, lines_deleted, total_lines, commit_count, change_type,
+         old_oid, new_oid, old_mode, new_mode, file_path_raw, run_id, old_path, lines_added_ws,
+         lines_deleted_ws)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )?;
+
+    for change in changes {
+        stmt.execute(params![
+            &change.commit_hash,
+            &change.file_path,
+            &change.lines_added,
+            &change.lines_deleted,
+            &change.total_lines,
+            &change.commit_count,
+            change.change_type.as_str(),
+            &change.old_oid,
+            &change.new_oid,
+            &change.old_mode,
+            &change.new_mode,
+            &change.file_path_raw,
+            run_id,
+            &change.old_path,
+            &change.lines_added_ws,
+            &change.lines_deleted_ws,
+        ])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_database() -> Database {
+        let database = Database::new(Path::new(":memory:")).unwrap();
+        database.create_tables().unwrap();
+        database
+    }
+
+    fn commit(hash: &str, parents: &[&str], commit_date: i64, topo_order: i64) -> CommitInfo {
+        CommitInfo {
+            commit_hash: hash.to_string(),
+            parent_hash: parents.first().map(|p| p.to_string()),
+            parent_hashes: parents.iter().map(|p| p.to_string()).collect(),
+            message: format!("commit {hash}"),
+            message_raw: None,
+            author_name: "Ana".to_string(),
+            author_email: "ana@example.com".to_string(),
+            commit_date,
+            commit_tz_offset: 0,
+            topo_order: Some(topo_order),
+        }
+    }
+
+    fn change(hash: &str, path: &str) -> FileChange {
+        FileChange {
+            commit_hash: hash.to_string(),
+            file_path: path.to_string(),
+            file_path_raw: None,
+            old_path: None,
+            lines_added: 1,
+            lines_deleted: 0,
+            lines_added_ws: None,
+            lines_deleted_ws: None,
+            total_lines: None,
+            commit_count: 1,
+            change_type: ChangeType::Modify,
+            old_oid: None,
+            new_oid: None,
+            old_mode: None,
+            new_mode: None,
+        }
+    }
+
+    fn node(hash: &str, parents: &[&str], commit_date: i64) -> TopoNode {
+        TopoNode {
+            commit_hash: hash.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            commit_date,
+            topo_order: None,
+        }
+    }
+
+    #[test]
+    fn topological_ranks_put_parents_first_despite_clock_skew() {
+        // b の日時は親の a より古い
+        let commits = [
+            node("c", &["a", "b"], 30),
+            node("b", &["a"], 5),
+            node("a", &[], 10),
+        ];
+        assert_eq!(topological_ranks(&commits), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn topological_ranks_ignore_unknown_parents() {
+        let commits = [node("b", &["missing"], 20), node("a", &[], 10)];
+        assert_eq!(topological_ranks(&commits), vec![1, 0]);
+    }
+
+    #[test]
+    fn parent_list_falls_back_to_first_parent() {
+        assert_eq!(
+            parent_list(Some("a b".to_string()), Some("a".to_string())),
+            vec!["a", "b"]
+        );
+        assert_eq!(parent_list(None, Some("a".to_string())), vec!["a"]);
+        assert!(parent_list(Some(String::new()), None).is_empty());
+    }
+
+    #[test]
+    fn commit_counts_follow_ancestry_across_runs() {
+        let mut database = memory_database();
+
+        // 1回目の実行は子のコミットだけを解析（--limit など）
+        database
+            .insert_commits(&[commit("child", &["root"], 20, 0)])
+            .unwrap();
+        // 2回目の実行で祖先が追加され、より大きな仮の順位が付く
+        database
+            .insert_commits(&[commit("root", &[], 10, 1)])
+            .unwrap();
+        database
+            .insert_file_changes(&[change("child", "a.rs"), change("root", "a.rs")])
+            .unwrap();
+
+        assert_eq!(database.recompute_topo_order().unwrap(), 2);
+        database.recompute_commit_counts().unwrap();
+
+        let history = database.file_history("a.rs").unwrap();
+        let counts: Vec<(&str, i32)> = history
+            .iter()
+            .map(|r| (r.commit.commit_hash.as_str(), r.change.commit_count))
+            .collect();
+        assert_eq!(counts, vec![("child", 2), ("root", 1)]);
+        assert_eq!(database.recompute_topo_order().unwrap(), 0);
+    }
+
+    #[test]
+    fn dir_rollups_use_the_configured_timezone() {
+        let mut database = memory_database();
+        // 2024-01-31 23:30 UTC（作成者のローカル時刻は +01:00 で 2月1日）
+        let mut late = commit("late", &[], 1_706_743_800, 0);
+        late.commit_tz_offset = 60;
+        database.insert_commits(&[late]).unwrap();
+        database
+            .insert_file_changes(&[change("late", "src/a.rs")])
+            .unwrap();
+
+        let bucket_start = |database: &mut Database, timezone| {
+            database
+                .refresh_dir_rollups(1, TimeBucket::Month, timezone)
+                .unwrap();
+            database
+                .conn
+                .query_row(
+                    "SELECT CAST(bucket_start AS VARCHAR) FROM dir_rollups",
+                    [],
+                    |row| row.get::<_, String>(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(bucket_start(&mut database, Timezone::Utc), "2024-01-01");
+        assert_eq!(bucket_start(&mut database, Timezone::Author), "2024-02-01");
+        assert_eq!(
+            bucket_start(&mut database, Timezone::Fixed(-300)),
+            "2024-01-01"
+        );
+        assert_eq!(
+            bucket_start(&mut database, Timezone::Fixed(540)),
+            "2024-02-01"
+        );
+    }
+
+    fn count(database: &Database, table: &str) -> i64 {
+        database
             .conn
-            .prepare("SELECT COUNT(DISTINCT file_path) FROM file_changes")?;
-        let count: i64 = stmt.query_row([], |row| row.get(0))?;
-        Ok(count as usize)
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn pruning_orphans_removes_rows_that_reference_them() {
+        let mut database = memory_database();
+        database
+            .insert_commits(&[commit("kept", &[], 10, 0), commit("gone", &["kept"], 20, 1)])
+            .unwrap();
+        database
+            .insert_file_changes(&[change("kept", "a.rs"), change("gone", "a.rs")])
+            .unwrap();
+        // gone を一括変更として分類し、リリース・失敗の記録からも参照する
+        database.refresh_commit_kinds(0, 0.9).unwrap();
+        database
+            .conn
+            .execute_batch(
+                "INSERT INTO release_commits VALUES ('v1', 'kept'), ('v1', 'gone'); \
+                 INSERT INTO analysis_errors (commit_hash, category, message) \
+                 VALUES ('gone', 'GIT', 'failed');",
+            )
+            .unwrap();
+
+        let unreachable = ["gone".to_string()];
+        assert_eq!(database.update_orphaned_commits(&unreachable).unwrap(), 1);
+        assert_eq!(database.prune_orphaned_commits().unwrap(), 1);
+
+        assert_eq!(count(&database, "commits"), 1);
+        assert_eq!(count(&database, "file_changes"), 1);
+        assert_eq!(count(&database, "commit_kinds"), 1);
+        assert_eq!(count(&database, "release_commits"), 1);
+        assert_eq!(count(&database, "analysis_errors"), 0);
+    }
+
+    #[test]
+    fn reports_leave_out_flagged_commits_unless_included() {
+        let mut database = memory_database();
+        database
+            .insert_commits(&[
+                commit("small", &[], 10, 0),
+                commit("bulk", &["small"], 20, 1),
+            ])
+            .unwrap();
+        database
+            .insert_file_changes(&[
+                change("small", "a.rs"),
+                change("bulk", "a.rs"),
+                change("bulk", "b.rs"),
+            ])
+            .unwrap();
+        assert_eq!(database.refresh_commit_kinds(1, 0.9).unwrap(), 1);
+
+        // 合計は分類に関係なくすべてのコミットを数える
+        assert_eq!(database.get_total_commits().unwrap(), 2);
+        assert_eq!(database.get_total_files().unwrap(), 2);
+        assert_eq!(database.get_commit_activity().unwrap().len(), 1);
+        assert_eq!(
+            database.get_commit_summaries(None, None, 10).unwrap().len(),
+            1
+        );
+        assert!(database.get_commit_files("bulk").unwrap().is_empty());
+
+        let database = database.with_flagged_commits(true);
+        assert_eq!(database.get_commit_activity().unwrap().len(), 2);
+        assert_eq!(database.get_file_commits("a.rs").unwrap().len(), 2);
+        assert_eq!(database.get_commit_files("bulk").unwrap().len(), 2);
     }
 }
@@ -9,10 +9,16 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         CREATE TABLE IF NOT EXISTS commits (
             commit_hash VARCHAR PRIMARY KEY,
             parent_hash VARCHAR,
+            parent_hashes VARCHAR,
             message TEXT NOT NULL,
+            message_raw BLOB,
             author_name VARCHAR NOT NULL,
             author_email VARCHAR NOT NULL,
             commit_date BIGINT NOT NULL,
+            commit_tz_offset INTEGER DEFAULT 0,
+            topo_order BIGINT,
+            orphaned_at TIMESTAMP,
+            run_id BIGINT,
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
         )
         "#,
@@ -30,6 +36,15 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
             total_lines INTEGER,
             commit_count INTEGER DEFAULT 1,
             change_type VARCHAR NOT NULL,
+            old_oid VARCHAR,
+            new_oid VARCHAR,
+            old_mode INTEGER,
+            new_mode INTEGER,
+            file_path_raw BLOB,
+            run_id BIGINT,
+            old_path VARCHAR,
+            lines_added_ws INTEGER,
+            lines_deleted_ws INTEGER,
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             PRIMARY KEY (commit_hash, file_path)
         )
@@ -37,12 +52,288 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // file_coupling テーブル（同時変更の集計結果）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS file_coupling (
+            file_a VARCHAR NOT NULL,
+            file_b VARCHAR NOT NULL,
+            co_changes INTEGER NOT NULL,
+            changes_a INTEGER NOT NULL,
+            changes_b INTEGER NOT NULL,
+            support DOUBLE NOT NULL,
+            confidence_ab DOUBLE NOT NULL,
+            confidence_ba DOUBLE NOT NULL,
+            PRIMARY KEY (file_a, file_b)
+        )
+        "#,
+        [],
+    )?;
+
+    // hotspots テーブル（変更頻度 × 複雑度）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS hotspots (
+            file_path VARCHAR PRIMARY KEY,
+            language VARCHAR NOT NULL,
+            change_count BIGINT NOT NULL,
+            lines_changed BIGINT NOT NULL,
+            lines BIGINT NOT NULL,
+            code_lines BIGINT NOT NULL,
+            max_depth BIGINT NOT NULL,
+            mean_depth DOUBLE NOT NULL,
+            functions BIGINT NOT NULL,
+            complexity DOUBLE NOT NULL,
+            score DOUBLE NOT NULL,
+            revision VARCHAR NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        [],
+    )?;
+
+    // dir_rollups テーブル（ディレクトリ × 期間の集計）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS dir_rollups (
+            dir_prefix VARCHAR NOT NULL,
+            depth INTEGER NOT NULL,
+            bucket VARCHAR NOT NULL,
+            bucket_start DATE NOT NULL,
+            commits BIGINT NOT NULL,
+            authors BIGINT NOT NULL,
+            lines_added BIGINT NOT NULL,
+            lines_deleted BIGINT NOT NULL,
+            files_touched BIGINT NOT NULL,
+            PRIMARY KEY (dir_prefix, depth, bucket, bucket_start)
+        )
+        "#,
+        [],
+    )?;
+
+    // activity_timeline テーブル（期間ごとの活動量）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS activity_timeline (
+            bucket VARCHAR NOT NULL,
+            bucket_start DATE NOT NULL,
+            timezone VARCHAR NOT NULL,
+            commits BIGINT NOT NULL,
+            active_authors BIGINT NOT NULL,
+            lines_added BIGINT NOT NULL,
+            lines_deleted BIGINT NOT NULL,
+            new_files BIGINT NOT NULL,
+            PRIMARY KEY (bucket, bucket_start)
+        )
+        "#,
+        [],
+    )?;
+
+    // 期間単位ごとのビュー
+    for (view, bucket) in [
+        ("activity_daily", "day"),
+        ("activity_weekly", "week"),
+        ("activity_monthly", "month"),
+    ] {
+        conn.execute(
+            &format!(
+                "CREATE OR REPLACE VIEW {view} AS \
+                 SELECT * EXCLUDE (bucket) FROM activity_timeline WHERE bucket = '{bucket}'"
+            ),
+            [],
+        )?;
+    }
+
+    // tree_snapshots テーブル（サンプリングしたリビジョン時点の規模）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS tree_snapshots (
+            commit_hash VARCHAR NOT NULL,
+            snapshot_date BIGINT NOT NULL,
+            label VARCHAR NOT NULL,
+            dimension VARCHAR NOT NULL,
+            key VARCHAR NOT NULL,
+            files BIGINT NOT NULL,
+            lines BIGINT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (commit_hash, dimension, key)
+        )
+        "#,
+        [],
+    )?;
+
+    // releases テーブル（タグ）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS releases (
+            tag_name VARCHAR PRIMARY KEY,
+            target_commit VARCHAR NOT NULL,
+            tagger_name VARCHAR,
+            tagger_email VARCHAR,
+            tag_date BIGINT NOT NULL,
+            message TEXT,
+            annotated BOOLEAN NOT NULL,
+            previous_tag VARCHAR,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        [],
+    )?;
+
+    // release_commits テーブル（前のリリースからの差分に含まれるコミット）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS release_commits (
+            tag_name VARCHAR NOT NULL,
+            commit_hash VARCHAR NOT NULL,
+            PRIMARY KEY (tag_name, commit_hash)
+        )
+        "#,
+        [],
+    )?;
+
+    // 中断した解析の再開用チェックポイント
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS analysis_checkpoints (
+            selection_key VARCHAR PRIMARY KEY,
+            total_commits BIGINT NOT NULL,
+            processed_commits BIGINT NOT NULL,
+            last_commit_hash VARCHAR NOT NULL,
+            base_topo_order BIGINT NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        [],
+    )?;
+
+    // --keep-going で記録した解析に失敗したコミット
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS analysis_errors (
+            commit_hash VARCHAR NOT NULL,
+            file_path VARCHAR,
+            category VARCHAR NOT NULL,
+            message TEXT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        [],
+    )?;
+
+    // analysis_runs テーブル（analyze の実行履歴）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS analysis_runs (
+            run_id BIGINT PRIMARY KEY,
+            started_at BIGINT NOT NULL,
+            finished_at BIGINT,
+            status VARCHAR NOT NULL,
+            tool_version VARCHAR NOT NULL,
+            repo_path VARCHAR NOT NULL,
+            remote_url VARCHAR,
+            refs VARCHAR NOT NULL,
+            options VARCHAR NOT NULL,
+            head_oids VARCHAR NOT NULL,
+            selected_commits BIGINT,
+            processed_commits BIGINT,
+            resumed_commits BIGINT,
+            failed_commits BIGINT,
+            orphaned_commits BIGINT,
+            error TEXT
+        )
+        "#,
+        [],
+    )?;
+
+    // commit_kinds テーブル（レポートから除外する一括変更・整形・リネームのみのコミット）
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS commit_kinds (
+            commit_hash VARCHAR NOT NULL,
+            kind VARCHAR NOT NULL,
+            PRIMARY KEY (commit_hash, kind)
+        )
+        "#,
+        [],
+    )?;
+
+    // 既存DBに不足しているカラムを追加
+    add_missing_columns(conn)?;
+
+    // 解析対象の参照から辿れるコミットのみのビュー（レポートはこちらを参照する）
+    create_active_views(conn)?;
+
     // インデックスを作成
     create_indexes(conn)?;
 
     Ok(())
 }
 
+/// 後から追加されたカラム（テーブル名, カラム名, 型）
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("commits", "commit_tz_offset", "INTEGER DEFAULT 0"),
+    ("commits", "topo_order", "BIGINT"),
+    ("commits", "orphaned_at", "TIMESTAMP"),
+    ("commits", "message_raw", "BLOB"),
+    ("commits", "run_id", "BIGINT"),
+    ("commits", "parent_hashes", "VARCHAR"),
+    ("file_changes", "old_oid", "VARCHAR"),
+    ("file_changes", "new_oid", "VARCHAR"),
+    ("file_changes", "old_mode", "INTEGER"),
+    ("file_changes", "new_mode", "INTEGER"),
+    ("file_changes", "file_path_raw", "BLOB"),
+    ("file_changes", "run_id", "BIGINT"),
+    ("file_changes", "old_path", "VARCHAR"),
+    ("file_changes", "lines_added_ws", "INTEGER"),
+    ("file_changes", "lines_deleted_ws", "INTEGER"),
+];
+
+/// 古いスキーマで作成されたDBにカラムを追加
+fn add_missing_columns(conn: &Connection) -> Result<()> {
+    for (table, column, column_type) in ADDED_COLUMNS {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {column} {column_type}"),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 孤立したコミット（履歴の書き換えで到達不能になったもの）と分類済みのコミットを除くビューを作成
+fn create_active_views(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE OR REPLACE VIEW active_commits AS \
+         SELECT * FROM commits WHERE orphaned_at IS NULL",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE OR REPLACE VIEW active_file_changes AS \
+         SELECT * FROM file_changes \
+         WHERE commit_hash IN (SELECT commit_hash FROM active_commits)",
+        [],
+    )?;
+
+    // commit_kinds で分類したコミットを除くビュー（ランキング系のレポートの既定）
+    conn.execute(
+        "CREATE OR REPLACE VIEW report_commits AS \
+         SELECT * FROM active_commits \
+         WHERE commit_hash NOT IN (SELECT commit_hash FROM commit_kinds)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE OR REPLACE VIEW report_file_changes AS \
+         SELECT * FROM active_file_changes \
+         WHERE commit_hash NOT IN (SELECT commit_hash FROM commit_kinds)",
+        [],
+    )?;
+
+    Ok(())
+}
+
 /// インデックスを作成
 fn create_indexes(conn: &Connection) -> Result<()> {
     conn.execute(
@@ -55,10 +346,20 @@ fn create_indexes(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_new_oid ON file_changes(new_oid)",
+        [],
+    )?;
+
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_commit_date ON commits(commit_date)",
         [],
     )?;
 
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_file_changes_run ON file_changes(run_id)",
+        [],
+    )?;
+
     Ok(())
 }
@@ -1,10 +1,15 @@
+use std::fmt;
 use thiserror::Error;
 
 /// カスタムエラー型
 #[derive(Error, Debug)]
 pub enum GitHistoryError {
-    #[error("Git repository not found: {0}")]
-    RepositoryNotFound(String),
+    #[error("Git repository not found: {path}: {source}")]
+    RepositoryNotFound {
+        path: String,
+        #[source]
+        source: git2::Error,
+    },
 
     #[error("Git error: {0}")]
     GitError(#[from] git2::Error),
@@ -18,8 +23,134 @@ pub enum GitHistoryError {
     #[error("Invalid configuration: {0}")]
     ConfigError(String),
 
+    #[error("Analysis cancelled after {processed} of {total} commits (run again to resume)")]
+    Cancelled { processed: usize, total: usize },
+
     #[error("Analysis error: {0}")]
     AnalysisError(String),
+
+    /// 処理中のコミット（とファイル）の情報を付与したエラー
+    #[error("{source} (commit {commit}{})", path_suffix(.path))]
+    InCommit {
+        commit: String,
+        path: Option<String>,
+        #[source]
+        source: Box<GitHistoryError>,
+    },
+}
+
+/// エラーの分類（終了コードと `analysis_errors.category` に使う）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Config,
+    Repository,
+    Git,
+    Database,
+    Io,
+    Analysis,
+    Cancelled,
+}
+
+impl ErrorCategory {
+    /// 文字列に変換
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Config => "CONFIG",
+            ErrorCategory::Repository => "REPOSITORY",
+            ErrorCategory::Git => "GIT",
+            ErrorCategory::Database => "DATABASE",
+            ErrorCategory::Io => "IO",
+            ErrorCategory::Analysis => "ANALYSIS",
+            ErrorCategory::Cancelled => "CANCELLED",
+        }
+    }
+
+    /// プロセスの終了コード（Ctrl-C による中断は慣例どおり130）
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCategory::Config => 2,
+            ErrorCategory::Repository => 3,
+            ErrorCategory::Git => 4,
+            ErrorCategory::Database => 5,
+            ErrorCategory::Io => 6,
+            ErrorCategory::Analysis => 7,
+            ErrorCategory::Cancelled => 130,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl GitHistoryError {
+    /// 処理中のコミットとファイルを付与（既に付与済みの場合は不足しているファイルのみ補う）
+    pub fn in_commit(self, commit: impl Into<String>, path: Option<&str>) -> Self {
+        match self {
+            GitHistoryError::InCommit {
+                commit,
+                path: None,
+                source,
+            } => GitHistoryError::InCommit {
+                commit,
+                path: path.map(str::to_string),
+                source,
+            },
+            error @ GitHistoryError::InCommit { .. } => error,
+            error => GitHistoryError::InCommit {
+                commit: commit.into(),
+                path: path.map(str::to_string),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// エラーが発生したコミット
+    pub fn commit(&self) -> Option<&str> {
+        match self {
+            GitHistoryError::InCommit { commit, .. } => Some(commit),
+            _ => None,
+        }
+    }
+
+    /// エラーが発生したファイルのパス
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            GitHistoryError::InCommit { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// エラーの分類
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            GitHistoryError::RepositoryNotFound { .. } => ErrorCategory::Repository,
+            GitHistoryError::GitError(_) => ErrorCategory::Git,
+            GitHistoryError::DatabaseError(_) => ErrorCategory::Database,
+            GitHistoryError::IoError(_) => ErrorCategory::Io,
+            GitHistoryError::ConfigError(_) => ErrorCategory::Config,
+            GitHistoryError::Cancelled { .. } => ErrorCategory::Cancelled,
+            GitHistoryError::AnalysisError(_) => ErrorCategory::Analysis,
+            GitHistoryError::InCommit { source, .. } => source.category(),
+        }
+    }
+
+    /// 1コミットの失敗として記録し、解析を続けられるエラーか（`--keep-going`）
+    pub fn is_per_commit(&self) -> bool {
+        matches!(
+            self.category(),
+            ErrorCategory::Git | ErrorCategory::Io | ErrorCategory::Analysis
+        )
+    }
+}
+
+/// 表示用のパス（`, path <パス>` 形式、なければ空文字列）
+fn path_suffix(path: &Option<String>) -> String {
+    path.as_deref()
+        .map(|path| format!(", path {}", path))
+        .unwrap_or_default()
 }
 
 pub type Result<T> = std::result::Result<T, GitHistoryError>;
@@ -1,4 +1,8 @@
 pub mod diff;
+pub mod remote;
 pub mod repository;
+pub mod text;
+pub mod tree;
 
-pub use repository::GitRepository;
+pub use diff::{DiffAlgorithm, DiffStatOptions, IgnoreWhitespace};
+pub use repository::{CommitOrder, CommitSelection, GitRepository, RepositoryKind};
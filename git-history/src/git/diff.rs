@@ -1,93 +1,553 @@
 use crate::database::models::{ChangeType, FileChange};
-use crate::error::Result;
-use git2::{Diff, Oid, Repository};
+use crate::error::{GitHistoryError, Result};
+use crate::git::text;
+use git2::{
+    Blob, Diff, DiffDelta, DiffFile, DiffFindOptions, DiffOptions, Oid, Patch, Repository, Tree,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// 差分アルゴリズム
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    /// git の既定（Myers）
+    #[default]
+    Myers,
+    /// patience（移動したブロックを追加・削除に分解しにくい）
+    Patience,
+    /// 差分が最小になるまで探索（遅い）
+    Minimal,
+}
+
+impl fmt::Display for DiffAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiffAlgorithm::Myers => "myers",
+            DiffAlgorithm::Patience => "patience",
+            DiffAlgorithm::Minimal => "minimal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for DiffAlgorithm {
+    type Err = GitHistoryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "myers" | "default" => Ok(DiffAlgorithm::Myers),
+            "patience" => Ok(DiffAlgorithm::Patience),
+            "minimal" => Ok(DiffAlgorithm::Minimal),
+            // libgit2 は histogram を実装していない
+            "histogram" => Err(GitHistoryError::ConfigError(
+                "The histogram diff algorithm is not supported by libgit2 (use patience or minimal)"
+                    .to_string(),
+            )),
+            _ => Err(GitHistoryError::ConfigError(format!(
+                "Unknown diff algorithm: {} (expected myers, patience or minimal)",
+                s
+            ))),
+        }
+    }
+}
+
+/// 空白の変更の無視のしかた
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreWhitespace {
+    /// 行末の空白（CRLF ⇔ LF の変換を含む）のみ無視（`git diff --ignore-space-at-eol`）
+    Eol,
+    /// 空白の量の変更を無視（`git diff -b`）
+    Change,
+    /// すべての空白を無視（`git diff -w`）
+    All,
+}
+
+impl fmt::Display for IgnoreWhitespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IgnoreWhitespace::Eol => "eol",
+            IgnoreWhitespace::Change => "change",
+            IgnoreWhitespace::All => "all",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for IgnoreWhitespace {
+    type Err = GitHistoryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "eol" => Ok(IgnoreWhitespace::Eol),
+            "change" => Ok(IgnoreWhitespace::Change),
+            "all" => Ok(IgnoreWhitespace::All),
+            _ => Err(GitHistoryError::ConfigError(format!(
+                "Unknown whitespace mode: {} (expected eol, change or all)",
+                s
+            ))),
+        }
+    }
+}
+
+/// 行数を数える差分の設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffStatOptions {
+    /// 差分アルゴリズム
+    pub algorithm: DiffAlgorithm,
+
+    /// ハンクの前後に含める行数（近いハンクの結合に影響する）
+    pub context_lines: u32,
+
+    /// 指定時は空白の変更を無視した行数も数える（変更されたファイルごとに差分をもう一度計算する）
+    pub ignore_whitespace: Option<IgnoreWhitespace>,
+
+    /// 削除と追加の組をリネーム（RENAME、old_path付きの1行）として記録する
+    pub detect_renames: bool,
+}
+
+impl Default for DiffStatOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: DiffAlgorithm::Myers,
+            context_lines: 3,
+            ignore_whitespace: None,
+            detect_renames: false,
+        }
+    }
+}
+
+impl DiffStatOptions {
+    /// libgit2 の差分オプションに変換
+    fn git_options(&self, ignore_whitespace: Option<IgnoreWhitespace>) -> DiffOptions {
+        let mut options = DiffOptions::new();
+        // 同じパスでのファイル⇔シンボリックリンクの変更を DELETE+ADD に分けず TYPE_CHANGE にする
+        options
+            .include_typechange(true)
+            .context_lines(self.context_lines)
+            .patience(self.algorithm == DiffAlgorithm::Patience)
+            .minimal(self.algorithm == DiffAlgorithm::Minimal);
+        match ignore_whitespace {
+            Some(IgnoreWhitespace::Eol) => options.ignore_whitespace_eol(true),
+            Some(IgnoreWhitespace::Change) => options.ignore_whitespace_change(true),
+            Some(IgnoreWhitespace::All) => options.ignore_whitespace(true),
+            None => &mut options,
+        };
+        options
+    }
+}
 
 /// ファイル変更情報を抽出
 pub fn extract_file_changes(
     repo: &Repository,
     commit_oid: Oid,
     commit_hash: &str,
+    options: &DiffStatOptions,
 ) -> Result<Vec<FileChange>> {
     let commit = repo.find_commit(commit_oid)?;
     let tree = commit.tree()?;
 
-    // 親コミットがある場合は差分を取る
-    let diff = if let Ok(parent) = commit.parent(0) {
-        let parent_tree = parent.tree()?;
-        repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?
-    } else {
-        // 初回コミットの場合（親がいない）
-        repo.diff_tree_to_tree(None, Some(&tree), None)?
+    // 親コミットがある場合は差分を取る（初回コミットは空のツリーとの差分）
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
     };
 
-    analyze_diff(&diff, commit_hash)
+    let diff = tree_diff(repo, parent_tree.as_ref(), &tree, options)?;
+    let mut changes = analyze_diff(&diff, commit_hash)?;
+
+    // 空白の変更を無視した行数は、同じdeltaのblob同士を空白を無視して比較し直して数える
+    if let Some(ignore_whitespace) = options.ignore_whitespace {
+        let counts = count_lines_ignoring_whitespace(
+            repo,
+            &diff,
+            commit_hash,
+            &mut options.git_options(Some(ignore_whitespace)),
+        )?;
+        for change in &mut changes {
+            let path = change
+                .file_path_raw
+                .as_deref()
+                .unwrap_or(change.file_path.as_bytes());
+            // サブモジュールなどblobでない変更は空白の影響を受けないので通常の行数を使う
+            let (added, deleted) = counts
+                .get(path)
+                .copied()
+                .unwrap_or((change.lines_added, change.lines_deleted));
+            change.lines_added_ws = Some(added);
+            change.lines_deleted_ws = Some(deleted);
+        }
+    }
+
+    Ok(changes)
+}
+
+/// ツリー間の差分（`detect_renames` の場合はリネームも検出する）
+fn tree_diff<'r>(
+    repo: &'r Repository,
+    old_tree: Option<&Tree>,
+    new_tree: &Tree,
+    options: &DiffStatOptions,
+) -> Result<Diff<'r>> {
+    let mut git_options = options.git_options(None);
+    let mut diff = repo.diff_tree_to_tree(old_tree, Some(new_tree), Some(&mut git_options))?;
+
+    // 削除と追加の組をリネームとして検出（類似度は git と同じ既定の50%）
+    if options.detect_renames {
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    }
+    Ok(diff)
 }
 
 /// Diffを解析してFileChangeのリストを作成
 fn analyze_diff(diff: &Diff, commit_hash: &str) -> Result<Vec<FileChange>> {
-    use std::collections::HashMap;
-
     // ファイルパスごとのデータを一度に収集
     #[derive(Default)]
     struct FileData {
         change_type: Option<ChangeType>,
-        lines_added: i32,
-        lines_deleted: i32,
+        old_oid: Option<String>,
+        new_oid: Option<String>,
+        old_mode: Option<i32>,
+        new_mode: Option<i32>,
+        old_path: Option<String>,
     }
 
-    let mut file_map: HashMap<String, FileData> = HashMap::new();
-
-    // diff.printで全ての情報を一度に収集
-    diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
-        let file_path = delta
-            .new_file()
-            .path()
-            .unwrap_or_else(|| delta.old_file().path().unwrap())
-            .to_string_lossy()
-            .to_string();
-
-        let data = file_map.entry(file_path).or_default();
+    // UTF-8でないパスを区別できるよう、元のバイト列をキーにする
+    let mut file_map: HashMap<Vec<u8>, FileData> = HashMap::new();
 
-        // 変更タイプを設定（最初の1回だけ）
-        if data.change_type.is_none() {
-            data.change_type = Some(ChangeType::from_git_delta(delta.status()));
-        }
+    // 変更種別・blob OID・ファイルモードはdeltaから取得
+    // （モードのみの変更やバイナリファイルはパッチ行を持たないため）
+    for delta in diff.deltas() {
+        let Some(path) = delta_path(&delta) else {
+            continue;
+        };
+        let data = file_map.entry(path.to_vec()).or_default();
 
-        // 行数をカウント
-        match line.origin() {
-            '+' => data.lines_added += 1,
-            '-' => data.lines_deleted += 1,
-            _ => {}
+        data.old_oid = blob_oid(&delta.old_file());
+        data.new_oid = blob_oid(&delta.new_file());
+        data.old_mode = blob_mode(&delta.old_file());
+        data.new_mode = blob_mode(&delta.new_file());
+        data.change_type = Some(detect_change_type(&delta));
+        if delta.status() == git2::Delta::Renamed {
+            data.old_path = delta
+                .old_file()
+                .path_bytes()
+                .map(|old| text::decode_path(old).0);
         }
+    }
 
-        true
-    })?;
+    let mut counts = count_lines(diff, commit_hash)?;
 
     // HashMapからVecに変換
     let changes: Vec<FileChange> = file_map
         .into_iter()
-        .map(|(file_path, data)| FileChange {
-            commit_hash: commit_hash.to_string(),
-            file_path,
-            lines_added: data.lines_added,
-            lines_deleted: data.lines_deleted,
-            total_lines: None,
-            commit_count: 1,
-            change_type: data.change_type.unwrap_or(ChangeType::Modify),
+        .map(|(path, data)| {
+            let (lines_added, lines_deleted) = counts.remove(&path).unwrap_or((0, 0));
+            let (file_path, file_path_raw) = text::decode_path(&path);
+            FileChange {
+                commit_hash: commit_hash.to_string(),
+                file_path,
+                file_path_raw,
+                old_path: data.old_path,
+                lines_added,
+                lines_deleted,
+                lines_added_ws: None,
+                lines_deleted_ws: None,
+                total_lines: None,
+                commit_count: 1,
+                change_type: data.change_type.unwrap_or(ChangeType::Modify),
+                old_oid: data.old_oid,
+                new_oid: data.new_oid,
+                old_mode: data.old_mode,
+                new_mode: data.new_mode,
+            }
         })
         .collect();
 
     Ok(changes)
 }
 
-/// ファイルのコミット回数を計算するヘルパー
-pub fn update_commit_counts(
-    changes: &mut [FileChange],
-    get_count_fn: impl Fn(&str) -> Result<i32>,
-) -> Result<()> {
-    for change in changes.iter_mut() {
-        let existing_count = get_count_fn(&change.file_path)?;
-        change.commit_count = existing_count + 1;
+/// パスごとの追加・削除行数を数える
+fn count_lines(diff: &Diff, commit_hash: &str) -> Result<HashMap<Vec<u8>, (i32, i32)>> {
+    let mut counts: HashMap<Vec<u8>, (i32, i32)> = HashMap::new();
+
+    // deltaごとにパッチを作成（失敗時のエラーには失敗したdeltaのパスを付ける）
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta_path(&delta) else {
+            continue;
+        };
+        let in_path = |e: git2::Error| {
+            GitHistoryError::from(e).in_commit(commit_hash, Some(&text::decode_path(path).0))
+        };
+
+        // バイナリファイルや内容の変わらない変更はパッチを持たない
+        let Some(patch) = Patch::from_diff(diff, index).map_err(in_path)? else {
+            continue;
+        };
+        let (_, added, deleted) = patch.line_stats().map_err(in_path)?;
+
+        let count = counts.entry(path.to_vec()).or_default();
+        count.0 += added as i32;
+        count.1 += deleted as i32;
+    }
+
+    Ok(counts)
+}
+
+/// パスごとに空白の変更を無視した追加・削除行数を数える（blobでない変更は含めない）
+fn count_lines_ignoring_whitespace(
+    repo: &Repository,
+    diff: &Diff,
+    commit_hash: &str,
+    git_options: &mut DiffOptions,
+) -> Result<HashMap<Vec<u8>, (i32, i32)>> {
+    let mut counts: HashMap<Vec<u8>, (i32, i32)> = HashMap::new();
+
+    for delta in diff.deltas() {
+        let Some(path) = delta_path(&delta) else {
+            continue;
+        };
+        let (old_file, new_file) = (delta.old_file(), delta.new_file());
+        if !is_blob(&old_file) || !is_blob(&new_file) {
+            continue;
+        }
+        let in_path = |e: git2::Error| {
+            GitHistoryError::from(e).in_commit(commit_hash, Some(&text::decode_path(path).0))
+        };
+
+        let old_blob = find_blob(repo, &old_file).map_err(in_path)?;
+        let new_blob = find_blob(repo, &new_file).map_err(in_path)?;
+        let patch = Patch::from_buffers(
+            old_blob.as_ref().map_or(&[][..], Blob::content),
+            old_file.path(),
+            new_blob.as_ref().map_or(&[][..], Blob::content),
+            new_file.path(),
+            Some(git_options),
+        )
+        .map_err(in_path)?;
+        let (_, added, deleted) = patch.line_stats().map_err(in_path)?;
+
+        let count = counts.entry(path.to_vec()).or_default();
+        count.0 += added as i32;
+        count.1 += deleted as i32;
+    }
+
+    Ok(counts)
+}
+
+/// 通常ファイル・実行ファイル・シンボリックリンク（または存在しない側）か
+fn is_blob(file: &DiffFile) -> bool {
+    !matches!(file.mode(), git2::FileMode::Commit | git2::FileMode::Tree)
+}
+
+/// deltaの片側のblobを取得（存在しない側はNone）
+fn find_blob<'r>(
+    repo: &'r Repository,
+    file: &DiffFile,
+) -> std::result::Result<Option<Blob<'r>>, git2::Error> {
+    if file.id().is_zero() {
+        return Ok(None);
+    }
+    repo.find_blob(file.id()).map(Some)
+}
+
+/// deltaのファイルパスをバイト列で取得（削除の場合は変更前のパス、どちらもなければNone）
+fn delta_path<'a>(delta: &DiffDelta<'a>) -> Option<&'a [u8]> {
+    delta
+        .new_file()
+        .path_bytes()
+        .or_else(|| delta.old_file().path_bytes())
+}
+
+/// 変更種別を判定（内容が同一でモードだけ変わった場合はModeChange）
+fn detect_change_type(delta: &DiffDelta) -> ChangeType {
+    let old_file = delta.old_file();
+    let new_file = delta.new_file();
+
+    if delta.status() == git2::Delta::Modified
+        && old_file.id() == new_file.id()
+        && old_file.mode() != new_file.mode()
+    {
+        return ChangeType::ModeChange;
+    }
+
+    ChangeType::from_git_delta(delta.status())
+}
+
+/// blob OIDを取得（存在しない側はNone）
+fn blob_oid(file: &DiffFile) -> Option<String> {
+    let oid = file.id();
+    if oid.is_zero() {
+        None
+    } else {
+        Some(oid.to_string())
+    }
+}
+
+/// ファイルモードを取得（存在しない側はNone）
+fn blob_mode(file: &DiffFile) -> Option<i32> {
+    match file.mode() {
+        git2::FileMode::Unreadable => None,
+        mode => Some(i32::from(mode)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+
+    /// `files`（パス, モード, 内容）だけを持つコミットを `parent` の子として作成
+    fn commit(repo: &Repository, parent: Option<Oid>, files: &[(&str, i32, &str)]) -> Oid {
+        let signature = Signature::new("Ana", "ana@example.com", &Time::new(100, 0)).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        for (path, mode, content) in files {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            builder.insert(path, blob, *mode).unwrap();
+        }
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parents: Vec<git2::Commit> = parent
+            .map(|oid| repo.find_commit(oid).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(None, &signature, &signature, "commit", &tree, &parent_refs)
+            .unwrap()
+    }
+
+    fn changes(repo: &Repository, oid: Oid, options: &DiffStatOptions) -> Vec<FileChange> {
+        let mut changes = extract_file_changes(repo, oid, &oid.to_string(), options).unwrap();
+        changes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        changes
+    }
+
+    #[test]
+    fn mode_and_type_changes_keep_both_sides() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let before = commit(
+            &repo,
+            None,
+            &[
+                ("link", 0o100644, "target\n"),
+                ("run.sh", 0o100644, "echo\n"),
+            ],
+        );
+        let after = commit(
+            &repo,
+            Some(before),
+            &[("link", 0o120000, "target"), ("run.sh", 0o100755, "echo\n")],
+        );
+
+        for detect_renames in [false, true] {
+            let options = DiffStatOptions {
+                detect_renames,
+                ..Default::default()
+            };
+            let changes = changes(&repo, after, &options);
+            assert_eq!(changes.len(), 2);
+
+            let link = &changes[0];
+            assert_eq!(link.change_type, ChangeType::TypeChange);
+            assert_eq!(link.old_mode, Some(0o100644));
+            assert_eq!(link.new_mode, Some(0o120000));
+            assert!(link.old_oid.is_some() && link.new_oid.is_some());
+
+            let script = &changes[1];
+            assert_eq!(script.change_type, ChangeType::ModeChange);
+            assert_eq!(script.old_mode, Some(0o100644));
+            assert_eq!(script.new_mode, Some(0o100755));
+            assert_eq!((script.lines_added, script.lines_deleted), (0, 0));
+        }
+    }
+
+    #[test]
+    fn reformatting_counts_only_as_raw_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let before = commit(
+            &repo,
+            None,
+            &[("main.rs", 0o100644, "fn main() {\n  println!(\"hi\");\n}\n")],
+        );
+        let after = commit(
+            &repo,
+            Some(before),
+            &[(
+                "main.rs",
+                0o100644,
+                "fn main() {\n    println!(\"hi\");  \r\n}\n",
+            )],
+        );
+
+        for ignore_whitespace in [IgnoreWhitespace::Change, IgnoreWhitespace::All] {
+            let options = DiffStatOptions {
+                ignore_whitespace: Some(ignore_whitespace),
+                ..Default::default()
+            };
+            let change = &changes(&repo, after, &options)[0];
+            assert_eq!((change.lines_added, change.lines_deleted), (1, 1));
+            assert_eq!(
+                (change.lines_added_ws, change.lines_deleted_ws),
+                (Some(0), Some(0))
+            );
+        }
+
+        // インデントの変更は行末の空白だけを無視しても残る
+        let options = DiffStatOptions {
+            ignore_whitespace: Some(IgnoreWhitespace::Eol),
+            ..Default::default()
+        };
+        let change = &changes(&repo, after, &options)[0];
+        assert_eq!(
+            (change.lines_added_ws, change.lines_deleted_ws),
+            (Some(1), Some(1))
+        );
+    }
+
+    #[test]
+    fn algorithm_and_context_lines_shape_the_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        // 一度だけ現れる行どうしを対応付ける patience は、入れ替わった U と V の間の行を作り直す
+        let before = commit(&repo, None, &[("a.txt", 0o100644, "U\nx\nx\nx\nx\nV\n")]);
+        let after = commit(
+            &repo,
+            Some(before),
+            &[("a.txt", 0o100644, "V\nx\nx\nx\nx\nU\n")],
+        );
+
+        let counts = |algorithm| {
+            let options = DiffStatOptions {
+                algorithm,
+                ..Default::default()
+            };
+            let change = &changes(&repo, after, &options)[0];
+            (change.lines_added, change.lines_deleted)
+        };
+        assert_eq!(counts(DiffAlgorithm::Myers), (2, 2));
+        assert_eq!(counts(DiffAlgorithm::Minimal), (2, 2));
+        assert_eq!(counts(DiffAlgorithm::Patience), (5, 5));
+
+        // 離れた2か所の変更は、前後の行数が少なければ別々のハンクになる
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "one\n2\n3\n4\n5\n6\n7\neight\n";
+        let hunks = |context_lines| {
+            let options = DiffStatOptions {
+                context_lines,
+                ..Default::default()
+            };
+            let patch = Patch::from_buffers(
+                old.as_bytes(),
+                None,
+                new.as_bytes(),
+                None,
+                Some(&mut options.git_options(None)),
+            )
+            .unwrap();
+            patch.num_hunks()
+        };
+        assert_eq!(hunks(3), 1);
+        assert_eq!(hunks(1), 2);
     }
-    Ok(())
 }
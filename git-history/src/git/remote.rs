@@ -0,0 +1,160 @@
+use crate::error::{GitHistoryError, Result};
+use git2::{AutotagOption, FetchOptions, FetchPrune, Repository};
+use std::path::{Path, PathBuf};
+
+/// ミラーのリモート名
+const MIRROR_REMOTE: &str = "origin";
+
+/// 既定で取得する参照（全ブランチとタグ）
+const DEFAULT_REFSPECS: [&str; 2] = ["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+/// リモートリポジトリのミラーキャッシュ設定
+#[derive(Debug, Clone)]
+pub struct MirrorOptions {
+    /// ベアミラーを置くディレクトリ
+    pub cache_dir: PathBuf,
+
+    /// 取得する履歴の深さ（Noneの場合は全履歴）
+    pub depth: Option<u32>,
+
+    /// 取得する参照（空の場合は全ブランチとタグ）
+    pub refspecs: Vec<String>,
+}
+
+impl Default for MirrorOptions {
+    fn default() -> Self {
+        Self {
+            cache_dir: default_cache_dir(),
+            depth: None,
+            refspecs: Vec::new(),
+        }
+    }
+}
+
+/// 既定のキャッシュディレクトリ（`$XDG_CACHE_HOME` または `~/.cache` 配下）
+pub fn default_cache_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    base.join("git-history").join("mirrors")
+}
+
+/// ローカルパスではなくクローンURLかどうか
+///
+/// `scheme://...` 形式と `user@host:path` 形式（scp風）をURLとみなす。
+pub fn is_remote_url(value: &str) -> bool {
+    if value.contains("://") {
+        return true;
+    }
+
+    match value.split_once(':') {
+        Some((host, _)) => host.contains('@') && !host.contains('/'),
+        None => false,
+    }
+}
+
+/// URLに対応するミラーのパス
+pub fn mirror_path(url: &str, cache_dir: &Path) -> PathBuf {
+    let name: String = url
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir.join(format!("{}.git", name.trim_end_matches(".git")))
+}
+
+/// ベアミラーを作成または更新し、そのパスを返す
+///
+/// 削除・強制更新されたブランチはミラーにも反映される（prune）。
+pub fn sync_mirror(url: &str, options: &MirrorOptions) -> Result<PathBuf> {
+    let path = mirror_path(url, &options.cache_dir);
+
+    let repo = if path.exists() {
+        let repo = Repository::open_bare(&path)?;
+        let remote_url = repo.find_remote(MIRROR_REMOTE)?.url().map(str::to_string);
+        if remote_url.as_deref() != Some(url) {
+            return Err(GitHistoryError::ConfigError(format!(
+                "Mirror cache {} belongs to a different remote: {}",
+                path.display(),
+                remote_url.unwrap_or_default()
+            )));
+        }
+        repo
+    } else {
+        std::fs::create_dir_all(&options.cache_dir)?;
+        let repo = Repository::init_bare(&path)?;
+        repo.remote_with_fetch(MIRROR_REMOTE, url, DEFAULT_REFSPECS[0])?;
+        repo
+    };
+
+    let refspecs: Vec<String> = if options.refspecs.is_empty() {
+        DEFAULT_REFSPECS.iter().map(|r| r.to_string()).collect()
+    } else {
+        options
+            .refspecs
+            .iter()
+            .map(|r| normalize_refspec(r))
+            .collect()
+    };
+
+    let mut remote = repo.find_remote(MIRROR_REMOTE)?;
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options
+        .prune(FetchPrune::On)
+        .download_tags(AutotagOption::None);
+    if let Some(depth) = options.depth {
+        fetch_options.depth(depth.min(i32::MAX as u32) as i32);
+    }
+
+    remote.fetch(&refspecs, Some(&mut fetch_options), None)?;
+
+    update_head(&repo, &mut remote)?;
+
+    Ok(path)
+}
+
+/// `refs/heads/main` や `main` を強制更新のrefspecに変換
+fn normalize_refspec(refspec: &str) -> String {
+    if refspec.contains(':') {
+        return refspec.to_string();
+    }
+
+    let name = if refspec.starts_with("refs/") {
+        refspec.to_string()
+    } else {
+        format!("refs/heads/{}", refspec)
+    };
+    format!("+{}:{}", name, name)
+}
+
+/// ミラーのHEADをリモートの既定ブランチ（取得できなければ最初のブランチ）に合わせる
+fn update_head(repo: &Repository, remote: &mut git2::Remote) -> Result<()> {
+    let default_branch = remote
+        .connect(git2::Direction::Fetch)
+        .and_then(|_| remote.default_branch())
+        .ok()
+        .and_then(|buf| buf.as_str().map(str::to_string));
+    let _ = remote.disconnect();
+
+    let head = match default_branch {
+        Some(name) if repo.find_reference(&name).is_ok() => Some(name),
+        _ => repo
+            .references_glob("refs/heads/*")?
+            .flatten()
+            .find_map(|reference| reference.name().map(str::to_string)),
+    };
+
+    if let Some(head) = head {
+        repo.set_head(&head)?;
+    }
+
+    Ok(())
+}
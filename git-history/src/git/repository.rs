@@ -1,29 +1,281 @@
-use crate::database::models::CommitInfo;
+use crate::database::models::{CommitInfo, ReleaseInfo};
 use crate::error::{GitHistoryError, Result};
-use git2::{Oid, Repository};
+use crate::git::text;
+use git2::{Oid, Repository, RevparseMode};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
+
+/// コミットの並び順
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitOrder {
+    /// コミット日時の新しい順（時計のずれたコミットがあると親子が逆転しうる）
+    #[default]
+    Time,
+    /// トポロジカル順（子が必ず親より先、新しい順）
+    Topological,
+    /// トポロジカル順の逆（親が必ず子より先、古い順）
+    Reverse,
+}
+
+impl CommitOrder {
+    /// revwalkのソート指定に変換
+    fn sort_mode(&self) -> git2::Sort {
+        match self {
+            CommitOrder::Time => git2::Sort::TIME,
+            CommitOrder::Topological => git2::Sort::TOPOLOGICAL | git2::Sort::TIME,
+            CommitOrder::Reverse => {
+                git2::Sort::TOPOLOGICAL | git2::Sort::TIME | git2::Sort::REVERSE
+            }
+        }
+    }
+}
+
+impl fmt::Display for CommitOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CommitOrder::Time => "time",
+            CommitOrder::Topological => "topo",
+            CommitOrder::Reverse => "reverse",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CommitOrder {
+    type Err = GitHistoryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "time" | "date" => Ok(CommitOrder::Time),
+            "topo" | "topological" => Ok(CommitOrder::Topological),
+            "reverse" | "oldest-first" => Ok(CommitOrder::Reverse),
+            _ => Err(GitHistoryError::ConfigError(format!(
+                "Unknown commit order: {} (expected time, topo or reverse)",
+                s
+            ))),
+        }
+    }
+}
+
+/// 解析対象コミットの選択条件
+#[derive(Debug, Clone, Default)]
+pub struct CommitSelection {
+    /// 起点となるローカルブランチ（range と同時に指定不可）
+    pub branch: Option<String>,
+
+    /// リビジョン範囲（`v1.0..v2.0`, `main...feature`, 単一のリビジョン）
+    pub range: Option<String>,
+
+    /// この日時以降のコミットのみ (Unix timestamp、コミッター日時で判定)
+    ///
+    /// 日時順（`CommitOrder::Time`）では、これより古いコミットに達した時点で走査を打ち切る
+    /// （`git log --since` と同じく、その先にある時計のずれた新しいコミットは含まれない）。
+    pub since: Option<i64>,
+
+    /// この日時以前のコミットのみ (Unix timestamp、コミッター日時で判定)
+    pub until: Option<i64>,
+
+    /// 最初の親のみを辿る
+    pub first_parent: bool,
+
+    /// 並び順（`limit` はこの順で先頭から数える）
+    pub order: CommitOrder,
+
+    /// コミット数の上限（日付の絞り込み後に適用）
+    pub limit: Option<usize>,
+}
+
+/// リポジトリの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepositoryKind {
+    /// 作業ツリーを持つ通常のリポジトリ
+    Standard,
+    /// 作業ツリーを持たないベアリポジトリ
+    Bare,
+    /// `git worktree add` で作成された追加の作業ツリー
+    Worktree,
+}
+
+impl fmt::Display for RepositoryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RepositoryKind::Standard => "standard",
+            RepositoryKind::Bare => "bare",
+            RepositoryKind::Worktree => "worktree",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 /// Gitリポジトリ管理
 pub struct GitRepository {
     repo: Repository,
+    /// shallow clone で親を取得していない境界のコミット（`shallow` ファイルに記録されたもの）
+    grafted: HashSet<Oid>,
 }
 
 impl GitRepository {
     /// リポジトリを開く
+    ///
+    /// 指定パスから親ディレクトリへ遡ってリポジトリを探す。
+    /// ベアリポジトリ（`.git` ディレクトリ自体を含む）と追加の作業ツリーにも対応する。
     pub fn open(path: &Path) -> Result<Self> {
-        let repo = Repository::open(path).map_err(|_| {
-            GitHistoryError::RepositoryNotFound(path.display().to_string())
-        })?;
+        let repo =
+            Repository::discover(path).map_err(|source| GitHistoryError::RepositoryNotFound {
+                path: path.display().to_string(),
+                source,
+            })?;
+        let grafted = read_grafted_commits(&repo)?;
+
+        Ok(Self { repo, grafted })
+    }
+
+    /// shallow clone の境界のコミットか（親がないため、差分はツリー全体の追加になる）
+    pub fn is_grafted(&self, oid: Oid) -> bool {
+        self.grafted.contains(&oid)
+    }
+
+    /// リポジトリの種類
+    pub fn kind(&self) -> RepositoryKind {
+        if self.repo.is_bare() {
+            RepositoryKind::Bare
+        } else if self.repo.is_worktree() {
+            RepositoryKind::Worktree
+        } else {
+            RepositoryKind::Standard
+        }
+    }
+
+    /// リポジトリのルート（作業ツリー、ベアリポジトリの場合はgitディレクトリ）
+    pub fn root(&self) -> &Path {
+        self.repo.workdir().unwrap_or_else(|| self.repo.path())
+    }
+
+    /// 選択条件に一致するコミットを指定の並び順で取得
+    pub fn get_commits(&self, selection: &CommitSelection) -> Result<Vec<Oid>> {
+        let mut revwalk = self.repo.revwalk()?;
+
+        let (tips, hidden) = self.resolve_selection(selection)?;
+        for tip in tips {
+            revwalk.push(tip)?;
+        }
+        for oid in hidden {
+            revwalk.hide(oid)?;
+        }
+
+        if selection.first_parent {
+            revwalk.simplify_first_parent()?;
+        }
+
+        revwalk.set_sorting(selection.order.sort_mode())?;
+
+        // コミットを収集
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            if let Some(max_limit) = selection.limit
+                && commits.len() >= max_limit
+            {
+                break;
+            }
+
+            let oid = oid?;
+            if selection.since.is_some() || selection.until.is_some() {
+                let commit_time = self.repo.find_commit(oid)?.time().seconds();
+                if selection.since.is_some_and(|since| commit_time < since) {
+                    // 日時順なら残りはすべてさらに古い
+                    if selection.order == CommitOrder::Time {
+                        break;
+                    }
+                    continue;
+                }
+                if selection.until.is_some_and(|until| commit_time > until) {
+                    continue;
+                }
+            }
+            commits.push(oid);
+        }
+
+        Ok(commits)
+    }
 
-        Ok(Self { repo })
+    /// コミットを親が先になるトポロジカル順（古い順）に並べ替え
+    ///
+    /// 与えられたコミット集合の中での親子関係のみを考慮し、
+    /// 同時に並べられるコミットはコミット日時の古い順とする。
+    pub fn topological_order(&self, oids: &[Oid]) -> Result<Vec<Oid>> {
+        let selected: HashSet<Oid> = oids.iter().copied().collect();
+
+        let mut pending_parents: HashMap<Oid, usize> = HashMap::new();
+        let mut children: HashMap<Oid, Vec<Oid>> = HashMap::new();
+        let mut commit_times: HashMap<Oid, i64> = HashMap::new();
+
+        for oid in &selected {
+            let commit = self.repo.find_commit(*oid)?;
+            commit_times.insert(*oid, commit.time().seconds());
+
+            let parents: HashSet<Oid> = commit
+                .parent_ids()
+                .filter(|parent| selected.contains(parent))
+                .collect();
+            pending_parents.insert(*oid, parents.len());
+            for parent in parents {
+                children.entry(parent).or_default().push(*oid);
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<(i64, Oid)>> = pending_parents
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(oid, _)| Reverse((commit_times[oid], *oid)))
+            .collect();
+
+        let mut ordered = Vec::with_capacity(selected.len());
+        while let Some(Reverse((_, oid))) = ready.pop() {
+            ordered.push(oid);
+            for child in children.get(&oid).into_iter().flatten() {
+                let count = pending_parents.entry(*child).or_default();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(Reverse((commit_times[child], *child)));
+                }
+            }
+        }
+
+        Ok(ordered)
     }
 
-    /// 全コミットを取得（新しい順）
-    pub fn get_commits(&self, branch: Option<&str>, limit: Option<usize>) -> Result<Vec<Oid>> {
+    /// 選択条件が解析対象とする参照の先端コミットを取得
+    pub fn analyzed_tips(&self, selection: &CommitSelection) -> Result<Vec<Oid>> {
+        Ok(self.resolve_selection(selection)?.0)
+    }
+
+    /// 先端コミットから辿れる全コミットを取得
+    pub fn reachable_commits(&self, tips: &[Oid]) -> Result<HashSet<Oid>> {
         let mut revwalk = self.repo.revwalk()?;
+        for tip in tips {
+            revwalk.push(*tip)?;
+        }
+        revwalk
+            .collect::<std::result::Result<HashSet<_>, _>>()
+            .map_err(Into::into)
+    }
 
-        // ブランチ指定がある場合は、そのブランチから開始
-        if let Some(branch_name) = branch {
+    /// 選択条件を (辿り始めるコミット, 除外するコミット) に解決
+    fn resolve_selection(&self, selection: &CommitSelection) -> Result<(Vec<Oid>, Vec<Oid>)> {
+        if selection.branch.is_some() && selection.range.is_some() {
+            return Err(GitHistoryError::ConfigError(
+                "branch and revision range cannot be combined".to_string(),
+            ));
+        }
+
+        if let Some(range) = &selection.range {
+            // リビジョン範囲から開始
+            self.resolve_range(range)
+        } else if let Some(branch_name) = &selection.branch {
+            // ブランチ指定がある場合は、そのブランチから開始
             let reference = self
                 .repo
                 .find_branch(branch_name, git2::BranchType::Local)?
@@ -32,68 +284,294 @@ impl GitRepository {
                 .ok_or_else(|| {
                     GitHistoryError::AnalysisError(format!("Branch {} not found", branch_name))
                 })?;
-            revwalk.push(reference)?;
+            Ok((vec![reference], Vec::new()))
         } else {
             // HEADから全コミットを辿る
-            revwalk.push_head()?;
+            Ok((vec![self.head_commit()?], Vec::new()))
         }
+    }
 
-        // 時系列順（新しい→古い）
-        revwalk.set_sorting(git2::Sort::TIME)?;
-
-        // コミットを収集
-        let mut commits = Vec::new();
-        for (i, oid) in revwalk.enumerate() {
-            if let Some(max_limit) = limit {
-                if i >= max_limit {
-                    break;
-                }
+    /// HEADが指すコミット
+    ///
+    /// コミットのないブランチを指すHEAD（空のベアリポジトリなど）は分かりやすいエラーにする。
+    fn head_commit(&self) -> Result<Oid> {
+        match self.repo.head() {
+            Ok(head) => Ok(head.peel_to_commit()?.id()),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                Err(GitHistoryError::AnalysisError(format!(
+                    "HEAD of {} does not point to a commit; use --branch or --range",
+                    self.root().display()
+                )))
             }
-            commits.push(oid?);
+            Err(e) => Err(e.into()),
         }
+    }
 
-        Ok(commits)
+    /// リビジョン範囲を (辿り始めるコミット, 除外するコミット) に解決
+    ///
+    /// `A..B` はBから辿れてAから辿れないコミット、`A...B` はどちらか一方からのみ辿れるコミット。
+    /// 十字にマージし合った履歴ではマージベースが複数あるので、そのすべてを除外する。
+    fn resolve_range(&self, range: &str) -> Result<(Vec<Oid>, Vec<Oid>)> {
+        let invalid = |e: git2::Error| {
+            GitHistoryError::ConfigError(format!("Invalid revision range '{}': {}", range, e))
+        };
+        let revspec = self.repo.revparse(range).map_err(invalid)?;
+
+        let from = revspec.from().map(|o| o.peel_to_commit()).transpose()?;
+        let to = revspec.to().map(|o| o.peel_to_commit()).transpose()?;
+
+        if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+            let (Some(from), Some(to)) = (from, to) else {
+                return Err(GitHistoryError::ConfigError(format!(
+                    "Invalid revision range '{}'",
+                    range
+                )));
+            };
+            let hidden = match self.repo.merge_bases(from.id(), to.id()) {
+                Ok(bases) => bases.iter().copied().collect(),
+                // 共通の祖先がない場合は両方の履歴すべてが対象
+                Err(e) if e.code() == git2::ErrorCode::NotFound => Vec::new(),
+                Err(e) => return Err(e.into()),
+            };
+            Ok((vec![from.id(), to.id()], hidden))
+        } else if revspec.mode().contains(RevparseMode::RANGE) {
+            // `A..` のように片側が省略された場合はHEADを補う
+            let tip = match to {
+                Some(to) => to.id(),
+                None => self.head_commit()?,
+            };
+            Ok((vec![tip], from.map(|f| f.id()).into_iter().collect()))
+        } else {
+            Ok((from.map(|f| f.id()).into_iter().collect(), Vec::new()))
+        }
     }
 
     /// コミット情報を抽出
     pub fn extract_commit_info(&self, oid: Oid) -> Result<CommitInfo> {
         let commit = self.repo.find_commit(oid)?;
 
-        // 親コミット（最初の親のみ）
+        // 親コミット（最初の親と、マージコミットのすべての親）
         let parent_hash = commit.parent_id(0).ok().map(|id| id.to_string());
+        let parent_hashes = commit.parent_ids().map(|id| id.to_string()).collect();
+
+        // コミットの encoding ヘッダー（なければUTF-8）
+        let encoding = commit.message_encoding();
 
         // 作成者情報
         let author = commit.author();
-        let author_name = author
-            .name()
-            .unwrap_or("Unknown")
-            .to_string();
-        let author_email = author
-            .email()
-            .unwrap_or("unknown@example.com")
-            .to_string();
+        let author_name = match author.name_bytes() {
+            [] => "Unknown".to_string(),
+            name => text::decode(name, encoding),
+        };
+        let author_email = match author.email_bytes() {
+            [] => "unknown@example.com".to_string(),
+            email => text::decode(email, encoding),
+        };
 
         // コミット日時（Unix timestamp）
         let commit_date = commit.time().seconds();
+        let commit_tz_offset = commit.time().offset_minutes();
 
-        // コミットメッセージ
-        let message = commit
-            .message()
-            .unwrap_or("(no message)")
-            .to_string();
+        // コミットメッセージ（表示用に変換できないバイト列は message_raw に保持）
+        let message_bytes = commit.message_bytes();
+        let message = text::decode(message_bytes, encoding);
+        let message_raw = text::raw_if_lossy(message_bytes, &message);
 
         Ok(CommitInfo {
             commit_hash: oid.to_string(),
             parent_hash,
+            parent_hashes,
             message,
+            message_raw,
             author_name,
             author_email,
             commit_date,
+            commit_tz_offset,
+            topo_order: None,
         })
     }
 
+    /// リビジョン（"HEAD", タグ名など）のコミットIDとツリーを取得
+    pub fn revision_tree(&self, revision: &str) -> Result<(Oid, git2::Tree<'_>)> {
+        let commit = self.repo.revparse_single(revision)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+        Ok((commit.id(), tree))
+    }
+
+    /// タグの一覧を取得（注釈付き・軽量タグの両方、コミット以外を指すタグは除外）
+    ///
+    /// 軽量タグの日時は対象コミットの日時とする。
+    pub fn get_tags(&self) -> Result<Vec<ReleaseInfo>> {
+        let mut tags = Vec::new();
+        for reference in self.repo.references_glob("refs/tags/*")? {
+            let reference = reference?;
+            let Some(name) = reference.shorthand() else {
+                continue;
+            };
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+
+            let release = match reference.peel_to_tag() {
+                Ok(tag) => {
+                    let tagger = tag.tagger();
+                    ReleaseInfo {
+                        tag_name: name.to_string(),
+                        target_commit: commit.id().to_string(),
+                        tagger_name: tagger.as_ref().and_then(|t| t.name()).map(String::from),
+                        tagger_email: tagger.as_ref().and_then(|t| t.email()).map(String::from),
+                        tag_date: tagger
+                            .as_ref()
+                            .map(|t| t.when().seconds())
+                            .unwrap_or_else(|| commit.time().seconds()),
+                        message: tag
+                            .message_bytes()
+                            .map(|m| text::decode(m, None).trim_end().to_string()),
+                        annotated: true,
+                    }
+                }
+                Err(_) => ReleaseInfo {
+                    tag_name: name.to_string(),
+                    target_commit: commit.id().to_string(),
+                    tagger_name: None,
+                    tagger_email: None,
+                    tag_date: commit.time().seconds(),
+                    message: None,
+                    annotated: false,
+                },
+            };
+            tags.push(release);
+        }
+        Ok(tags)
+    }
+
     /// 内部のRepositoryへの参照を取得
     pub fn inner(&self) -> &Repository {
         &self.repo
     }
 }
+
+/// shallow clone の境界のコミットを `shallow` ファイルから読み込む（shallow でなければ空）
+fn read_grafted_commits(repo: &Repository) -> Result<HashSet<Oid>> {
+    if !repo.is_shallow() {
+        return Ok(HashSet::new());
+    }
+
+    // 追加の作業ツリーでは `commondir` が指す共有のgitディレクトリにある
+    let git_dir = repo.path();
+    let common_dir = match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(relative) => git_dir.join(relative.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    };
+    let shallow = std::fs::read_to_string(common_dir.join("shallow"))?;
+    shallow
+        .split_whitespace()
+        .map(|line| Oid::from_str(line).map_err(Into::into))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+
+    /// 空のツリーを持つコミットを `time` の日時で作成
+    fn commit(repo: &Repository, parents: &[Oid], time: i64) -> Oid {
+        let signature = Signature::new("Ana", "ana@example.com", &Time::new(time, 0)).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(None, &signature, &signature, "commit", &tree, &parent_refs)
+            .unwrap()
+    }
+
+    #[test]
+    fn topological_order_puts_parents_first_despite_clock_skew() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let root = commit(&repo, &[], 100);
+        // 子の日時が親より古い
+        let skewed = commit(&repo, &[root], 50);
+        let side = commit(&repo, &[root], 200);
+        let merge = commit(&repo, &[skewed, side], 300);
+
+        let git_repo = GitRepository::open(dir.path()).unwrap();
+        let order = git_repo
+            .topological_order(&[merge, side, skewed, root])
+            .unwrap();
+
+        // 同時に並べられる skewed と side は日時の古い順
+        assert_eq!(order, [root, skewed, side, merge]);
+    }
+
+    #[test]
+    fn topological_order_ignores_parents_outside_the_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let root = commit(&repo, &[], 100);
+        let first = commit(&repo, &[root], 300);
+        let second = commit(&repo, &[root], 200);
+
+        let git_repo = GitRepository::open(dir.path()).unwrap();
+        let order = git_repo.topological_order(&[first, second]).unwrap();
+
+        assert_eq!(order, [second, first]);
+    }
+
+    #[test]
+    fn symmetric_range_hides_every_merge_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let root = commit(&repo, &[], 100);
+        let a1 = commit(&repo, &[root], 200);
+        let b1 = commit(&repo, &[root], 210);
+        // 互いにマージし合った後のマージベースは a1 と b1 の2つ
+        let a2 = commit(&repo, &[a1, b1], 300);
+        let b2 = commit(&repo, &[b1, a1], 310);
+        let a3 = commit(&repo, &[a2], 400);
+        let b3 = commit(&repo, &[b2], 410);
+        repo.reference("refs/heads/a", a3, true, "test").unwrap();
+        repo.reference("refs/heads/b", b3, true, "test").unwrap();
+
+        let git_repo = GitRepository::open(dir.path()).unwrap();
+        let selection = CommitSelection {
+            range: Some("a...b".to_string()),
+            ..Default::default()
+        };
+        let mut commits = git_repo.get_commits(&selection).unwrap();
+        commits.sort();
+        let mut expected = vec![a2, a3, b2, b3];
+        expected.sort();
+        assert_eq!(commits, expected);
+    }
+
+    #[test]
+    fn since_stops_the_time_ordered_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let root = commit(&repo, &[], 100);
+        let first = commit(&repo, &[root], 200);
+        let second = commit(&repo, &[first], 300);
+        repo.reference("refs/heads/main", second, true, "test")
+            .unwrap();
+
+        let git_repo = GitRepository::open(dir.path()).unwrap();
+        let commits = |order| {
+            let selection = CommitSelection {
+                range: Some("main".to_string()),
+                since: Some(150),
+                order,
+                ..Default::default()
+            };
+            git_repo.get_commits(&selection).unwrap()
+        };
+        assert_eq!(commits(CommitOrder::Time), [second, first]);
+        assert_eq!(commits(CommitOrder::Topological), [second, first]);
+        assert_eq!(commits(CommitOrder::Reverse), [first, second]);
+    }
+}
@@ -0,0 +1,82 @@
+use encoding_rs::{Encoding, UTF_8};
+
+/// コミットのテキスト（メッセージ・作成者名）を表示用の文字列に変換
+///
+/// `encoding` ヘッダー（例: `ISO-8859-1`, `Shift_JIS`）があればその文字コードで、
+/// なければUTF-8として解釈する。解釈できないバイトは置換文字になる。
+pub fn decode(bytes: &[u8], encoding: Option<&str>) -> String {
+    let encoding = encoding
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, _had_errors) = encoding.decode_without_bom_handling(bytes);
+    text.into_owned()
+}
+
+/// 表示用の文字列から元のバイト列を復元できない場合のみ元のバイト列を返す
+///
+/// UTF-8として正しいバイト列はNone（表示用の文字列と同一のため保存不要）。
+pub fn raw_if_lossy(bytes: &[u8], text: &str) -> Option<Vec<u8>> {
+    if bytes == text.as_bytes() {
+        None
+    } else {
+        Some(bytes.to_vec())
+    }
+}
+
+/// パスのバイト列を表示用の文字列と元のバイト列（文字列と異なる場合のみ）に変換
+///
+/// UTF-8でないバイトは `\xNN` 形式、バックスラッシュ自体は `\\` で表す（git の quotePath と同じ）。
+/// 文字列中の `\` は必ずエスケープの始まりになるので、異なるパスが同じ文字列になることはない。
+pub fn decode_path(bytes: &[u8]) -> (String, Option<Vec<u8>>) {
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(&chunk.valid().replace('\\', "\\\\"));
+        for byte in chunk.invalid() {
+            text.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    let raw = raw_if_lossy(bytes, &text);
+    (text, raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_uses_the_encoding_header() {
+        assert_eq!(decode(b"caf\xe9", Some("ISO-8859-1")), "café");
+        assert_eq!(decode(b"\x93\xfa\x96\x7b", Some("Shift_JIS")), "日本");
+        assert_eq!(decode("café".as_bytes(), None), "café");
+        // 未知のラベルはUTF-8として扱う
+        assert_eq!(decode(b"caf\xe9", Some("no-such-charset")), "caf\u{fffd}");
+    }
+
+    #[test]
+    fn raw_is_kept_only_when_lossy() {
+        assert_eq!(raw_if_lossy(b"abc", "abc"), None);
+        assert_eq!(raw_if_lossy(b"caf\xe9", "café"), Some(b"caf\xe9".to_vec()));
+    }
+
+    #[test]
+    fn decode_path_escapes_invalid_bytes() {
+        assert_eq!(
+            decode_path(b"src/main.rs"),
+            ("src/main.rs".to_string(), None)
+        );
+        assert_eq!(
+            decode_path(b"caf\xe9.txt"),
+            (r"caf\xe9.txt".to_string(), Some(b"caf\xe9.txt".to_vec()))
+        );
+    }
+
+    #[test]
+    fn decode_path_keeps_distinct_paths_distinct() {
+        // UTF-8として正しい `\xe9` という文字列と、0xE9 のバイトを含むパス
+        let literal = decode_path(br"caf\xe9.txt");
+        let raw_byte = decode_path(b"caf\xe9.txt");
+        assert_ne!(literal.0, raw_byte.0);
+        assert_eq!(literal.0, r"caf\\xe9.txt");
+        assert_eq!(literal.1, Some(br"caf\xe9.txt".to_vec()));
+    }
+}
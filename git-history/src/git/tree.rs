@@ -0,0 +1,58 @@
+use crate::error::Result;
+use crate::git::text;
+use git2::{ObjectType, Oid, Repository, Tree};
+
+/// ツリー内のファイル内容を読み込む（存在しない・ファイルでない場合はNone）
+///
+/// パスはコミットに記録されたバイト列で受け取り、UTF-8でない名前やバックスラッシュを含む名前も
+/// そのまま辿れるようにする（`file_path_raw` があればそちら、なければ `file_path` を渡す）。
+pub fn read_blob(repo: &Repository, tree: &Tree, file_path: &[u8]) -> Result<Option<Vec<u8>>> {
+    let mut components = file_path.split(|&b| b == b'/').peekable();
+    let mut current = tree.clone();
+    while let Some(name) = components.next() {
+        let Some((kind, id)) = current.get_name_bytes(name).map(|e| (e.kind(), e.id())) else {
+            return Ok(None);
+        };
+        match kind {
+            Some(ObjectType::Blob) if components.peek().is_none() => {
+                let blob = repo.find_blob(id)?;
+                return Ok(Some(blob.content().to_vec()));
+            }
+            Some(ObjectType::Tree) if components.peek().is_some() => {
+                current = repo.find_tree(id)?;
+            }
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// ツリー内の全ファイル（blob）をパスとOIDで列挙（サブモジュールは除外）
+///
+/// UTF-8でない名前も飛ばさないよう、パスはバイト列で組み立てて `text::decode_path` で変換する。
+pub fn walk_blobs(repo: &Repository, tree: &Tree, mut f: impl FnMut(&str, Oid)) -> Result<()> {
+    walk_tree(repo, tree, &mut Vec::new(), &mut f)
+}
+
+/// `walk_blobs` の再帰部分（`prefix` は親ディレクトリのパス）
+fn walk_tree(
+    repo: &Repository,
+    tree: &Tree,
+    prefix: &mut Vec<u8>,
+    f: &mut impl FnMut(&str, Oid),
+) -> Result<()> {
+    for entry in tree.iter() {
+        let len = prefix.len();
+        prefix.extend_from_slice(entry.name_bytes());
+        match entry.kind() {
+            Some(ObjectType::Blob) => f(&text::decode_path(prefix).0, entry.id()),
+            Some(ObjectType::Tree) => {
+                prefix.push(b'/');
+                walk_tree(repo, &repo.find_tree(entry.id())?, prefix, f)?;
+            }
+            _ => {}
+        }
+        prefix.truncate(len);
+    }
+    Ok(())
+}
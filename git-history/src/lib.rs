@@ -1,9 +1,13 @@
+pub mod analysis;
 pub mod analyzer;
+pub mod cancellation;
 pub mod config;
 pub mod database;
 pub mod error;
 pub mod git;
+pub mod progress;
 
 pub use analyzer::{AnalysisResult, Analyzer};
+pub use cancellation::CancellationToken;
 pub use config::Config;
-pub use error::{GitHistoryError, Result};
+pub use error::{ErrorCategory, GitHistoryError, Result};
@@ -1,5 +1,18 @@
+mod commands;
+
 use clap::{Parser, Subcommand};
-use git_history::{Analyzer, Config};
+use commands::analyze::AnalyzeArgs;
+use commands::coupling::CouplingArgs;
+use commands::diff::DiffArgs;
+use commands::hotspots::HotspotsArgs;
+use commands::ownership::OwnershipArgs;
+use commands::releases::ReleasesArgs;
+use commands::serve::ServeArgs;
+use commands::snapshots::SnapshotsArgs;
+use commands::timeline::TimelineArgs;
+use commands::tui::TuiArgs;
+use git_history::ErrorCategory;
+use git_history::config::file::ConfigFile;
 use std::path::PathBuf;
 use std::process;
 
@@ -8,6 +21,10 @@ use std::process;
 #[command(version = "0.1.0")]
 #[command(about = "Git repository history analyzer with DuckDB", long_about = None)]
 struct Cli {
+    /// Configuration file (default: ./git-history.toml, on top of ~/.config/git-history/git-history.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -15,69 +32,62 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Analyze Git repository and store in DuckDB
-    Analyze {
-        /// Repository path (default: current directory)
-        #[arg(short, long, default_value = ".")]
-        repo: PathBuf,
-
-        /// Output database path
-        #[arg(short, long, default_value = "git-history.db")]
-        output: PathBuf,
-
-        /// Target branch (default: all branches from HEAD)
-        #[arg(short, long)]
-        branch: Option<String>,
-
-        /// Incremental mode (append to existing DB)
-        #[arg(short, long)]
-        incremental: bool,
-
-        /// Verbose output
-        #[arg(short, long)]
-        verbose: bool,
-
-        /// Limit number of commits to analyze
-        #[arg(short, long)]
-        limit: Option<usize>,
-    },
+    Analyze(AnalyzeArgs),
+
+    /// Report code ownership, bus factor and knowledge-loss risk
+    Ownership(OwnershipArgs),
+
+    /// Report files that frequently change together (logical coupling)
+    Coupling(CouplingArgs),
+
+    /// Rank files by change frequency combined with code complexity
+    Hotspots(HotspotsArgs),
+
+    /// Show commits, active authors and churn per day, week or month
+    Timeline(TimelineArgs),
+
+    /// Record codebase size (files, lines per language/directory) at sampled revisions
+    Snapshots(SnapshotsArgs),
+
+    /// Summarize tags/releases and generate a changelog
+    Releases(ReleasesArgs),
+
+    /// Compare two periods or two databases (hotspots, authors, directories, churn)
+    Diff(DiffArgs),
+
+    /// Serve a local dashboard and JSON API over the analysis database
+    Serve(ServeArgs),
+
+    /// Browse files, commits and authors in a terminal UI
+    Tui(TuiArgs),
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    let result = match cli.command {
-        Commands::Analyze {
-            repo,
-            output,
-            branch,
-            incremental,
-            verbose,
-            limit,
-        } => {
-            // 設定を作成
-            let config = Config::new(repo, output)
-                .with_branch(branch)
-                .with_incremental(incremental)
-                .with_verbose(verbose)
-                .with_limit(limit);
-
-            // アナライザーを作成して実行
-            let analyzer = Analyzer::new(config);
-            analyzer.analyze()
-        }
-    };
-
-    match result {
-        Ok(analysis_result) => {
-            println!("\n✨ Success!");
-            if analysis_result.total_commits == 0 {
-                println!("⚠️  No commits found in the repository.");
-            }
-            process::exit(0);
-        }
-        Err(e) => {
-            eprintln!("\n❌ Error: {}", e);
-            process::exit(1);
+    // 設定ファイル（ユーザー設定 → プロジェクト設定）を読み込み、CLIの値はその上に適用する
+    let result =
+        ConfigFile::discover(cli.config.as_deref()).and_then(|settings| match cli.command {
+            Commands::Analyze(args) => commands::analyze::run(&args, &settings),
+            Commands::Ownership(args) => commands::ownership::run(&args, &settings),
+            Commands::Coupling(args) => commands::coupling::run(&args, &settings),
+            Commands::Hotspots(args) => commands::hotspots::run(&args, &settings),
+            Commands::Timeline(args) => commands::timeline::run(&args, &settings),
+            Commands::Snapshots(args) => commands::snapshots::run(&args, &settings),
+            Commands::Releases(args) => commands::releases::run(&args, &settings),
+            Commands::Diff(args) => commands::diff::run(&args, &settings),
+            Commands::Serve(args) => commands::serve::run(&args, &settings),
+            Commands::Tui(args) => commands::tui::run(&args, &settings),
+        });
+
+    // 終了コードはエラーの分類ごとに分ける（README の Exit codes を参照）
+    if let Err(e) = result {
+        let category = e.category();
+        if category == ErrorCategory::Cancelled {
+            eprintln!("\n⏸  {}", e);
+        } else {
+            eprintln!("\n❌ Error [{}]: {}", category, e);
         }
+        process::exit(category.exit_code());
     }
 }
@@ -0,0 +1,108 @@
+use crate::database::CommitInfo;
+use crate::error::GitHistoryError;
+use std::fmt;
+use std::time::Duration;
+
+/// 解析の工程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// リモートリポジトリのミラーを取得・更新
+    SyncingMirror,
+    /// リポジトリとデータベースを開く
+    Opening,
+    /// 解析対象のコミットを列挙
+    CollectingCommits,
+    /// コミットとファイル変更を抽出して保存
+    ProcessingCommits,
+    /// 到達不能になったコミットを検出
+    DetectingOrphans,
+    /// 累積コミット回数を再計算
+    ComputingCommitCounts,
+    /// 一括変更・整形・リネームのみのコミットを分類
+    ClassifyingCommits,
+    /// ディレクトリ集計を更新
+    RefreshingRollups,
+    /// 時系列集計を更新
+    RefreshingTimeline,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::SyncingMirror => "Syncing mirror",
+            Phase::Opening => "Opening repository and database",
+            Phase::CollectingCommits => "Collecting commits",
+            Phase::ProcessingCommits => "Processing commits",
+            Phase::DetectingOrphans => "Detecting orphaned commits",
+            Phase::ComputingCommitCounts => "Computing commit counts",
+            Phase::ClassifyingCommits => "Classifying bulk and formatting commits",
+            Phase::RefreshingRollups => "Refreshing directory rollups",
+            Phase::RefreshingTimeline => "Refreshing activity timeline",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// コミット処理の進捗（再開時はスキップしたコミットを含まない）
+#[derive(Debug, Clone, Copy)]
+pub struct CommitProgress {
+    /// 処理済みのコミット数
+    pub processed: usize,
+    /// 処理対象の全コミット数
+    pub total: usize,
+    /// コミット処理の開始からの経過時間
+    pub elapsed: Duration,
+}
+
+impl CommitProgress {
+    /// スループット（コミット/秒）
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.processed as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// 残り時間の見積もり（まだ1件も処理していない場合はNone）
+    pub fn eta(&self) -> Option<Duration> {
+        if self.processed == 0 {
+            return None;
+        }
+        let remaining = self.total.saturating_sub(self.processed) as f64;
+        Some(Duration::from_secs_f64(
+            remaining / self.throughput().max(f64::EPSILON),
+        ))
+    }
+}
+
+/// 解析の進捗を受け取るオブザーバー
+///
+/// ライブラリは標準出力に何も書かないため、表示が必要な場合は呼び出し側で実装する。
+/// 全メソッドにデフォルト実装があるので、必要なものだけ実装すればよい。
+pub trait ProgressObserver {
+    /// 工程の開始
+    fn phase_started(&self, _phase: Phase) {}
+
+    /// 工程の完了
+    fn phase_finished(&self, _phase: Phase, _elapsed: Duration) {}
+
+    /// コミット1件の抽出が完了
+    fn commit_analyzed(&self, _commit: &CommitInfo) {}
+
+    /// コミットの解析に失敗（`--keep-going` で記録して続行する場合）
+    fn commit_failed(&self, _error: &GitHistoryError) {}
+
+    /// 中断した前回の実行のチェックポイントから再開
+    fn resumed_from_checkpoint(&self, _skipped: usize, _total: usize) {}
+
+    /// コミットバッチの保存が完了（開始時に processed = 0 でも呼ばれる）
+    fn commits_processed(&self, _progress: &CommitProgress) {}
+}
+
+/// 何も表示しないオブザーバー
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {}